@@ -0,0 +1,54 @@
+//! Hot paths of writing an .off against the ways they used to be computed.
+//!
+//! `cargo bench` runs them; each group times the current path next to the old one on the same
+//! inputs.
+
+use criterion::Criterion;
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use e8_omni_off::MirrorSet;
+use e8_omni_off::Point;
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+
+/// Vertices of polytopes with distinct, repeated and zero coordinates, each paired with the D8
+/// of another one
+fn points_and_d8s() -> Vec<(Point, e8_omni_off::D8)> {
+    let mut points: Vec<Point> = [
+        MirrorSet::A0,
+        MirrorSet::A0 | MirrorSet::C,
+        MirrorSet::B0 | MirrorSet::M,
+    ]
+    .into_iter()
+    .flat_map(|mirrors| mirrors.vertex_orbits())
+    .flat_map(|(vertex, _)| vertex.orbit.iter().take(1000))
+    .collect();
+    let mut rng = SmallRng::seed_from_u64(0);
+    points.shuffle(&mut rng);
+    let d8s: Vec<_> = points.iter().rev().map(|point| point.element).collect();
+    points.into_iter().zip(d8s).collect()
+}
+
+fn point_mul(c: &mut Criterion) {
+    let inputs = points_and_d8s();
+    let mut group = c.benchmark_group("Point * D8");
+    group.bench_function("composed", |b| {
+        b.iter(|| {
+            for &(point, d8) in &inputs {
+                black_box(black_box(point) * d8);
+            }
+        })
+    });
+    group.bench_function("through Vec8", |b| {
+        b.iter(|| {
+            for &(point, d8) in &inputs {
+                black_box(Point::new(black_box(point).vec() * d8));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, point_mul);
+criterion_main!(benches);
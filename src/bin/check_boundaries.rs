@@ -0,0 +1,34 @@
+use clap::Parser;
+use e8_omni_off::Compression;
+use e8_omni_off::check_boundaries;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Check that the boundary of the boundary of every face of an .off written with
+/// `e8-omni-off --oriented` is zero
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    /// Oriented .off, decompressed if it ends in .gz, .zst or .xz
+    off: PathBuf,
+}
+
+fn run() -> Result<(), e8_omni_off::Error> {
+    let cli = Cli::parse();
+    let reader = Compression::from_path(&cli.off).reader(File::open(&cli.off)?)?;
+    check_boundaries(BufReader::new(reader))?;
+    println!("{}: boundaries of boundaries are zero", cli.off.display());
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
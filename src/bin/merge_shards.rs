@@ -0,0 +1,40 @@
+use clap::Parser;
+use e8_omni_off::Compression;
+use e8_omni_off::merge_shards;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Stitch shards written with `e8-omni-off --shard i/n` into one .off
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    /// Shard files, in any order
+    #[arg(required = true)]
+    shards: Vec<PathBuf>,
+
+    /// Write .off, compressed if it ends in .gz, .zst or .xz
+    #[arg(short, long, value_name = "FILE")]
+    off: PathBuf,
+}
+
+fn run() -> Result<(), e8_omni_off::Error> {
+    let cli = Cli::parse();
+    let mut writer =
+        Compression::from_path(&cli.off).writer(BufWriter::new(File::create(&cli.off)?))?;
+    merge_shards(&cli.shards, &mut writer)?;
+    writer.finish()?.flush()?;
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
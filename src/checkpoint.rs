@@ -0,0 +1,198 @@
+use crate::coords::Coordinates;
+use crate::e8::MirrorSet;
+use crate::error::Error;
+use crate::error::Result;
+use crate::off::OffPosition;
+use crate::shard::Shard;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// How far a [`MirrorSet::write_off`] got: every element before `position` is written, taking
+/// up the first `bytes` bytes of the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub mirrors: MirrorSet,
+    pub dim_limit: usize,
+    pub shard: Option<Shard>,
+    pub oriented: bool,
+    pub coordinates: Coordinates,
+    pub position: OffPosition,
+    pub bytes: u64,
+}
+
+impl Checkpoint {
+    /// Where checkpoints for the .off at `off` are kept
+    pub fn path_for(off: &Path) -> PathBuf {
+        let mut path = off.as_os_str().to_owned();
+        path.push(".ckpt");
+        path.into()
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let mut fields = [None; 6];
+        let mut shard = None;
+        let mut oriented = false;
+        let mut coordinates = Coordinates::default();
+        for line in text.lines() {
+            let bad_line = || Error::Checkpoint(format!("bad line {line:?} in {}", path.display()));
+            let (key, value) = line.split_once(' ').ok_or_else(bad_line)?;
+            if key == "shard" {
+                shard = Some(value.parse()?);
+                continue;
+            }
+            if key == "oriented" {
+                oriented = match value {
+                    "0" => false,
+                    "1" => true,
+                    _ => return Err(bad_line()),
+                };
+                continue;
+            }
+            if key == "coordinates" {
+                coordinates = value.parse()?;
+                continue;
+            }
+            let index = [
+                "mirrors",
+                "dim_limit",
+                "dim",
+                "face_type",
+                "element",
+                "bytes",
+            ]
+            .iter()
+            .position(|k| *k == key)
+            .ok_or_else(bad_line)?;
+            fields[index] = Some(value.parse::<u64>().map_err(|_| bad_line())?);
+        }
+        let [
+            Some(mirrors),
+            Some(dim_limit),
+            Some(dim),
+            Some(face_type),
+            Some(element),
+            Some(bytes),
+        ] = fields
+        else {
+            return Err(Error::Checkpoint(format!(
+                "missing fields in {}",
+                path.display()
+            )));
+        };
+        Ok(Self {
+            mirrors: u8::try_from(mirrors)
+                .ok()
+                .and_then(MirrorSet::from_bits)
+                .ok_or_else(|| Error::Checkpoint(format!("bad mirrors {mirrors}")))?,
+            dim_limit: dim_limit as usize,
+            shard,
+            oriented,
+            coordinates,
+            position: OffPosition {
+                dim: dim as usize,
+                face_type: face_type as usize,
+                element,
+            },
+            bytes,
+        })
+    }
+
+    /// Write the checkpoint to a temporary file and move it over `path`, so that `path` always
+    /// holds a complete checkpoint
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let mut text = format!(
+            "mirrors {}\ndim_limit {}\ndim {}\nface_type {}\nelement {}\nbytes {}\n",
+            self.mirrors.bits(),
+            self.dim_limit,
+            self.position.dim,
+            self.position.face_type,
+            self.position.element,
+            self.bytes,
+        );
+        if let Some(shard) = self.shard {
+            text += &format!("shard {shard}\n");
+        }
+        if self.oriented {
+            text += "oriented 1\n";
+        }
+        if self.coordinates != Coordinates::default() {
+            text += &format!("coordinates {}\n", self.coordinates);
+        }
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Whether this checkpoint was made by a write of the same polytope
+    pub fn check(
+        &self,
+        mirrors: MirrorSet,
+        dim_limit: usize,
+        shard: Option<Shard>,
+        oriented: bool,
+        coordinates: Coordinates,
+    ) -> Result<()> {
+        if self.mirrors != mirrors || self.dim_limit != dim_limit {
+            return Err(Error::Checkpoint(format!(
+                "checkpoint is for {:?} up to dimension {}, not {:?} up to dimension {}",
+                self.mirrors, self.dim_limit, mirrors, dim_limit
+            )));
+        }
+        if self.shard != shard {
+            return Err(Error::Checkpoint(format!(
+                "checkpoint is for shard {:?}, not {:?}",
+                self.shard, shard
+            )));
+        }
+        if self.oriented != oriented {
+            return Err(Error::Checkpoint(format!(
+                "checkpoint is for a write with oriented {}, not {}",
+                self.oriented, oriented
+            )));
+        }
+        if self.coordinates != coordinates {
+            return Err(Error::Checkpoint(format!(
+                "checkpoint is for {} coordinates, not {}",
+                self.coordinates, coordinates
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoint_roundtrip() {
+        let path = std::env::temp_dir().join(format!("e8-checkpoint-{}.ckpt", std::process::id()));
+        let checkpoint = Checkpoint {
+            mirrors: MirrorSet::A0 | MirrorSet::C,
+            dim_limit: 8,
+            shard: Some(Shard {
+                index: 3,
+                count: 10,
+            }),
+            oriented: true,
+            coordinates: Coordinates::Even,
+            position: OffPosition {
+                dim: 3,
+                face_type: 1,
+                element: 12345678901,
+            },
+            bytes: 98765432109876,
+        };
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap(), checkpoint);
+
+        let text = fs::read_to_string(&path).unwrap();
+        fs::write(&path, text.replace("oriented 1", "oriented yes")).unwrap();
+        assert!(matches!(Checkpoint::load(&path), Err(Error::Checkpoint(_))));
+        fs::remove_file(&path).unwrap();
+    }
+}
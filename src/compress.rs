@@ -0,0 +1,169 @@
+use crate::error::Error;
+use crate::error::Result;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::fmt::Display;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use xz2::read::XzDecoder;
+use xz2::write::XzEncoder;
+
+/// Streaming compression of an .off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+const GZIP_LEVEL: u32 = 6;
+const ZSTD_LEVEL: i32 = 3;
+const XZ_LEVEL: u32 = 6;
+
+impl Compression {
+    /// Compression implied by the extension of `path`: `.gz`, `.zst` or `.xz`
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Self::Gzip,
+            Some("zst") => Self::Zstd,
+            Some("xz") => Self::Xz,
+            _ => Self::None,
+        }
+    }
+
+    pub fn writer<W: Write>(self, inner: W) -> Result<CompressWriter<W>> {
+        Ok(match self {
+            Self::None => CompressWriter::None(inner),
+            Self::Gzip => {
+                CompressWriter::Gzip(GzEncoder::new(inner, flate2::Compression::new(GZIP_LEVEL)))
+            }
+            Self::Zstd => CompressWriter::Zstd(zstd::Encoder::new(inner, ZSTD_LEVEL)?),
+            Self::Xz => CompressWriter::Xz(XzEncoder::new(inner, XZ_LEVEL)),
+        })
+    }
+
+    /// Reader of the decompressed contents of `inner`, which may be several compressed streams
+    /// one after another
+    pub fn reader<'a>(self, inner: impl Read + 'a) -> Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Self::None => Box::new(inner),
+            Self::Gzip => Box::new(MultiGzDecoder::new(inner)),
+            Self::Zstd => Box::new(zstd::Decoder::new(inner)?),
+            Self::Xz => Box::new(XzDecoder::new_multi_decoder(inner)),
+        })
+    }
+
+    /// Size of `data` once compressed
+    pub fn compressed_len(self, data: &[u8]) -> Result<u64> {
+        let mut writer = self.writer(Vec::new())?;
+        writer.write_all(data)?;
+        Ok(writer.finish()?.len() as u64)
+    }
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::None => "none",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Xz => "xz",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Compression {
+    type Err = Error;
+    fn from_str(st: &str) -> Result<Self> {
+        match st {
+            "none" => Ok(Self::None),
+            "gzip" | "gz" => Ok(Self::Gzip),
+            "zstd" | "zst" => Ok(Self::Zstd),
+            "xz" => Ok(Self::Xz),
+            _ => Err(Error::ParseCompression(st.to_owned())),
+        }
+    }
+}
+
+/// Writer compressing into `W`, which must be [`finish`](Self::finish)ed to write the end of the
+/// compressed stream
+pub enum CompressWriter<W: Write> {
+    None(W),
+    Gzip(GzEncoder<W>),
+    Zstd(zstd::Encoder<'static, W>),
+    Xz(XzEncoder<W>),
+}
+
+impl<W: Write> CompressWriter<W> {
+    pub fn finish(self) -> Result<W> {
+        Ok(match self {
+            Self::None(inner) => inner,
+            Self::Gzip(encoder) => encoder.finish()?,
+            Self::Zstd(encoder) => encoder.finish()?,
+            Self::Xz(encoder) => encoder.finish()?,
+        })
+    }
+}
+
+impl<W: Write> Write for CompressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::None(inner) => inner.write(buf),
+            Self::Gzip(encoder) => encoder.write(buf),
+            Self::Zstd(encoder) => encoder.write(buf),
+            Self::Xz(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::None(inner) => inner.flush(),
+            Self::Gzip(encoder) => encoder.flush(),
+            Self::Zstd(encoder) => encoder.flush(),
+            Self::Xz(encoder) => encoder.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compression_roundtrip() {
+        let data = b"3 0 1 2\n3 0 2 3\n".repeat(1000);
+        for compression in [
+            Compression::None,
+            Compression::Gzip,
+            Compression::Zstd,
+            Compression::Xz,
+        ] {
+            // two streams one after another, like merged shards
+            let mut writer = compression.writer(Vec::new()).unwrap();
+            writer.write_all(&data).unwrap();
+            let mut compressed = writer.finish().unwrap();
+            let mut writer = compression.writer(Vec::new()).unwrap();
+            writer.write_all(&data).unwrap();
+            compressed.extend(writer.finish().unwrap());
+
+            let mut decompressed = Vec::new();
+            compression
+                .reader(&compressed[..])
+                .unwrap()
+                .read_to_end(&mut decompressed)
+                .unwrap();
+            assert!(
+                decompressed == [&data[..], &data[..]].concat(),
+                "{compression}"
+            );
+            if compression != Compression::None {
+                assert!(compression.compressed_len(&data).unwrap() < data.len() as u64 / 10);
+            }
+        }
+    }
+}
@@ -0,0 +1,395 @@
+//! Coordinate systems the vertices of an .off can be written in.
+//!
+//! The crate's own coordinates are twice Conway and Sloane's even coordinate system, so that every
+//! lattice vector has integer coordinates: all even or all odd, with a sum divisible by 4, and the
+//! roots have norm 2√2. The other systems are computed from those when a vertex line is written.
+
+use crate::e8::Mirror;
+use crate::e8::MirrorSet;
+use crate::error::Error;
+use crate::error::Result;
+use crate::off::write_spaced;
+use crate::point::Coord;
+use crate::point::Orbit;
+use crate::point::Vec8;
+use std::fmt::Display;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Decimal places of [`Coordinates::Unit`] and [`Coordinates::Circumradius`] unless given
+pub const DEFAULT_PRECISION: usize = 9;
+
+/// Coordinate system of the vertices of an .off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Coordinates {
+    /// The crate's own integer coordinates
+    #[default]
+    Lattice,
+    /// Conway and Sloane's even coordinate system: all integers or all halves of odd integers,
+    /// with an even sum, and roots of norm √2
+    Even,
+    /// The odd coordinate system: the even one with the sign of the last coordinate changed
+    Odd,
+    /// Dynkin labels, the dot products with the simple roots in the order of [`Mirror::ALL`],
+    /// which are the coordinates in the basis of fundamental weights
+    ///
+    /// [`MirrorSet::vertex`] has a 1 for every ringed mirror and a 0 for the others.
+    Dynkin,
+    /// Decimals with this many places, scaled to edge length 1
+    Unit(usize),
+    /// Decimals with this many places, scaled to circumradius 1
+    Circumradius(usize),
+}
+
+impl Coordinates {
+    /// The same system with `precision` decimal places, if it is written in decimals
+    pub fn with_precision(self, precision: usize) -> Result<Self> {
+        match self {
+            Self::Unit(_) => Ok(Self::Unit(precision)),
+            Self::Circumradius(_) => Ok(Self::Circumradius(precision)),
+            _ => Err(Error::Coordinates(format!(
+                "{self} coordinates are not written in decimals"
+            ))),
+        }
+    }
+
+    /// Decimal places of lengths given in this system
+    pub fn precision(self) -> usize {
+        match self {
+            Self::Unit(precision) | Self::Circumradius(precision) => precision,
+            _ => DEFAULT_PRECISION,
+        }
+    }
+}
+
+/// Written `unit` or `circumradius` with the default precision, and `unit:6` otherwise
+impl Display for Coordinates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, precision) = match *self {
+            Self::Lattice => ("lattice", None),
+            Self::Even => ("even", None),
+            Self::Odd => ("odd", None),
+            Self::Dynkin => ("dynkin", None),
+            Self::Unit(precision) => ("unit", Some(precision)),
+            Self::Circumradius(precision) => ("circumradius", Some(precision)),
+        };
+        match precision {
+            Some(precision) if precision != DEFAULT_PRECISION => write!(f, "{name}:{precision}"),
+            _ => write!(f, "{name}"),
+        }
+    }
+}
+
+impl FromStr for Coordinates {
+    type Err = Error;
+    fn from_str(st: &str) -> Result<Self> {
+        let bad = || {
+            Error::Coordinates(format!(
+                "unknown coordinates {st:?}, expected one of lattice even odd dynkin unit \
+                 circumradius"
+            ))
+        };
+        let (name, precision) = match st.split_once(':') {
+            Some((name, precision)) => (name, Some(precision.parse().map_err(|_| bad())?)),
+            None => (st, None),
+        };
+        let coordinates = match name {
+            "lattice" => Self::Lattice,
+            "even" => Self::Even,
+            "odd" => Self::Odd,
+            "dynkin" => Self::Dynkin,
+            "unit" => Self::Unit(DEFAULT_PRECISION),
+            "circumradius" => Self::Circumradius(DEFAULT_PRECISION),
+            _ => return Err(bad()),
+        };
+        match precision {
+            Some(precision) => coordinates.with_precision(precision),
+            None => Ok(coordinates),
+        }
+    }
+}
+
+impl MirrorSet {
+    /// Square of the edge length in lattice coordinates, twice the distance from
+    /// [`vertex`](Self::vertex) to any ringed mirror, or 0 if no mirror is ringed
+    pub fn edge_length_squared(self) -> i64 {
+        let vertex = self.vertex().vec().map(i64::from);
+        self.mirrors().next().map_or(0, |mirror| {
+            let pole = mirror.pole().map(i64::from);
+            let dot = vertex.dot(&pole);
+            4 * dot * dot / pole.dot(&pole)
+        })
+    }
+
+    /// Square of the circumradius in lattice coordinates, the norm of
+    /// [`vertex`](Self::vertex)
+    pub fn circumradius_squared(self) -> i64 {
+        let vertex = self.vertex().vec().map(i64::from);
+        vertex.dot(&vertex)
+    }
+}
+
+/// `x` with `precision` decimal places, without negative zeros
+pub(crate) fn decimal(x: f64, precision: usize) -> String {
+    let x = format!("{x:.precision$}");
+    match x.strip_prefix('-') {
+        Some(abs) if abs.bytes().all(|b| b == b'0' || b == b'.') => abs.to_owned(),
+        _ => x,
+    }
+}
+
+/// [`Coordinates`] for the vertices of one polytope
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct VertexFormat {
+    coordinates: Coordinates,
+    /// Factor taking lattice coordinates to decimals
+    scale: f64,
+}
+
+impl VertexFormat {
+    pub(crate) fn new(coordinates: Coordinates, mirrors: MirrorSet) -> Result<Self> {
+        // only decimals are scaled
+        let scale = match coordinates {
+            Coordinates::Unit(_) | Coordinates::Circumradius(_) => {
+                let (num, den) = mirrors.length_squared_scale(coordinates)?;
+                (num as f64 / den as f64).sqrt()
+            }
+            _ => 1.0,
+        };
+        Ok(Self { coordinates, scale })
+    }
+
+    /// Coordinate `x` of a lattice vector, in a system that takes coordinates one by one
+    fn coordinate(&self, x: Coord) -> String {
+        match self.coordinates {
+            Coordinates::Lattice | Coordinates::Dynkin => x.to_string(),
+            Coordinates::Even | Coordinates::Odd => {
+                let sign = if x < 0 { "-" } else { "" };
+                let half = if x % 2 == 0 { "" } else { ".5" };
+                format!("{sign}{}{half}", x.abs() / 2)
+            }
+            Coordinates::Unit(precision) | Coordinates::Circumradius(precision) => {
+                decimal(x as f64 * self.scale, precision)
+            }
+        }
+    }
+
+    /// Write the vertex at `vec`, in lattice coordinates, as a line of the .off
+    pub(crate) fn write_vertex(&self, mut writer: impl Write, mut vec: Vec8) -> Result<()> {
+        match self.coordinates {
+            Coordinates::Lattice => write_spaced(&mut writer, vec.iter())?,
+            Coordinates::Dynkin => write_spaced(
+                &mut writer,
+                Mirror::ALL.iter().map(|m| {
+                    let dot = vec.dot(&m.pole());
+                    debug_assert_eq!(dot % 4, 0, "inexact Dynkin label of {vec}");
+                    dot / 4
+                }),
+            )?,
+            _ => {
+                if self.coordinates == Coordinates::Odd {
+                    vec[7] = -vec[7];
+                }
+                write_spaced(&mut writer, vec.iter().map(|&x| self.coordinate(x)))?
+            }
+        }
+        writeln!(writer)?;
+        Ok(())
+    }
+
+    /// Total length of the vertex lines of the points of `orbit`
+    pub(crate) fn orbit_length(&self, orbit: Orbit) -> u64 {
+        let size = orbit.size();
+        if self.coordinates == Coordinates::Dynkin {
+            return Mirror::ALL
+                .iter()
+                .map(|m| Self::label_length(orbit, m.pole()))
+                .sum::<u64>()
+                + 8 * size;
+        }
+        // every nonzero coordinate is negative in exactly half of the orbit, by flipping its sign
+        // together with that of another nonzero coordinate, or alone if there is a zero
+        let (digits, signs) = orbit.rep.iter().fold((0, 0), |(digits, signs), &x| {
+            let (positive, negative) = (self.coordinate(x.abs()), self.coordinate(-x.abs()));
+            (
+                digits + positive.len() as u64,
+                signs + (negative.len() - positive.len()) as u64,
+            )
+        });
+        // 7 spaces and a newline
+        (digits + 8) * size + signs * size / 2
+    }
+
+    /// Total length of the Dynkin labels for `pole` of the points of `orbit`
+    fn label_length(orbit: Orbit, pole: Vec8) -> u64 {
+        // each point is the image of a point of the orbit under as many permutations with even
+        // sign changes as any other, so the labels are those of all the images of one point
+        let Some(first) = orbit.nth(0) else {
+            return 0;
+        };
+        let vec = first.vec();
+        let length = |dot: Coord| (dot / 4).to_string().len() as u128;
+        let support: Vec<_> = pole.iter().copied().filter(|&x| x != 0).collect();
+        let (total, images) = if support.len() == 8 {
+            // the permutation only moves the signs of the pole around, and its product with
+            // those of the change stays that of the pole
+            let parity = support.iter().filter(|&&x| x < 0).count() % 2;
+            (0..1u32 << 8)
+                .filter(|signs| signs.count_ones() as usize % 2 == parity)
+                .map(|signs| {
+                    length(
+                        (0..8)
+                            .map(|i| if signs >> i & 1 == 1 { -vec[i] } else { vec[i] })
+                            .sum(),
+                    )
+                })
+                .fold((0, 0), |(total, images), l| (total + l, images + 1))
+        } else {
+            // the two coordinates under the pole are any two others, with any signs, since the
+            // other six make the number of sign changes even
+            let [a, b] = [support[0].abs(), support[1].abs()];
+            let mut total = 0;
+            for i in 0..8 {
+                for j in (0..8).filter(|&j| j != i) {
+                    for (x, y) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                        total += length(x * a * vec[i] + y * b * vec[j]);
+                    }
+                }
+            }
+            (total, 8 * 7 * 4)
+        };
+        (orbit.size() as u128 * total / images) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(coordinates: Coordinates, mirrors: MirrorSet, vecs: &[Vec8]) -> String {
+        let format = VertexFormat::new(coordinates, mirrors).unwrap();
+        let mut out = Vec::new();
+        for &vec in vecs {
+            format.write_vertex(&mut out, vec).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    fn vertices(mirrors: MirrorSet) -> Vec<Vec8> {
+        mirrors
+            .vertex_orbits()
+            .iter()
+            .flat_map(|(vertex, _)| vertex.orbit.iter().map(|point| point.vec()))
+            .collect()
+    }
+
+    #[test]
+    fn coordinate_systems() {
+        let mirrors = MirrorSet::A0 | MirrorSet::C;
+        let vertex = [mirrors.vertex().vec()];
+        let line = |coordinates| lines(coordinates, mirrors, &vertex);
+        assert_eq!(line(Coordinates::Lattice), "1 -1 -1 -1 -1 -1 -3 7\n");
+        assert_eq!(
+            line(Coordinates::Even),
+            "0.5 -0.5 -0.5 -0.5 -0.5 -0.5 -1.5 3.5\n"
+        );
+        assert_eq!(
+            line(Coordinates::Odd),
+            "0.5 -0.5 -0.5 -0.5 -0.5 -0.5 -1.5 -3.5\n"
+        );
+        assert_eq!(line(Coordinates::Dynkin), "1 0 0 0 0 0 1 0\n");
+
+        // the neighbour across A0 is at distance 1
+        assert_eq!(mirrors.edge_length_squared(), 8);
+        let neighbour = [(mirrors.vertex() * Mirror::A0.mat()).vec()];
+        let parse = |line: String| -> Vec<f64> {
+            line.split_whitespace()
+                .map(|x| x.parse().unwrap())
+                .collect()
+        };
+        let unit = Coordinates::Unit(DEFAULT_PRECISION);
+        let edge: f64 = parse(line(unit))
+            .iter()
+            .zip(parse(lines(unit, mirrors, &neighbour)))
+            .map(|(a, b)| (a - b).powi(2))
+            .sum();
+        assert!((edge - 1.0).abs() < 1e-9, "{edge}");
+        assert!(VertexFormat::new(unit, MirrorSet::empty()).is_err());
+
+        assert_eq!(mirrors.circumradius_squared(), 64);
+        assert_eq!(
+            line(Coordinates::Circumradius(3)),
+            "0.125 -0.125 -0.125 -0.125 -0.125 -0.125 -0.375 0.875\n"
+        );
+        assert_eq!(
+            line(Coordinates::Unit(1)),
+            "0.4 -0.4 -0.4 -0.4 -0.4 -0.4 -1.1 2.5\n"
+        );
+
+        for coordinates in [
+            "lattice",
+            "even",
+            "odd",
+            "dynkin",
+            "unit",
+            "unit:3",
+            "circumradius",
+            "circumradius:0",
+        ] {
+            assert_eq!(
+                coordinates.parse::<Coordinates>().unwrap().to_string(),
+                coordinates
+            );
+        }
+        assert_eq!(
+            "circumradius:9".parse::<Coordinates>().unwrap(),
+            Coordinates::Circumradius(DEFAULT_PRECISION)
+        );
+        assert_eq!("unit:3".parse::<Coordinates>().unwrap().precision(), 3);
+        assert_eq!(Coordinates::Even.precision(), DEFAULT_PRECISION);
+        assert!("weights".parse::<Coordinates>().is_err());
+        assert!("even:3".parse::<Coordinates>().is_err());
+        assert!("unit:".parse::<Coordinates>().is_err());
+    }
+
+    #[test]
+    fn orbit_lengths_exact() {
+        for mirrors in [
+            MirrorSet::A0,
+            MirrorSet::A0 | MirrorSet::C,
+            MirrorSet::B0 | MirrorSet::M,
+        ] {
+            let vertices = vertices(mirrors);
+            for coordinates in [
+                Coordinates::Lattice,
+                Coordinates::Even,
+                Coordinates::Odd,
+                Coordinates::Dynkin,
+                Coordinates::Unit(DEFAULT_PRECISION),
+                Coordinates::Unit(1),
+                Coordinates::Circumradius(0),
+            ] {
+                let format = VertexFormat::new(coordinates, mirrors).unwrap();
+                let orbits = mirrors.vertex_orbits();
+                let length: u64 = orbits
+                    .iter()
+                    .map(|(vertex, _)| format.orbit_length(vertex.orbit))
+                    .sum();
+                assert_eq!(
+                    length,
+                    lines(coordinates, mirrors, &vertices).len() as u64,
+                    "{mirrors:?} {coordinates}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dynkin_labels_of_roots() {
+        // the roots are the vertices of 4_21, and the labels of the highest one are those of A0
+        let labels = lines(Coordinates::Dynkin, MirrorSet::A0, &vertices(MirrorSet::A0));
+        assert_eq!(labels.lines().count(), 240);
+        assert!(labels.lines().any(|line| line == "1 0 0 0 0 0 0 0"));
+        assert!(labels.lines().any(|line| line == "-1 0 0 0 0 0 0 0"));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::e8::Ring::XX;
 use crate::e8::Ring::oo;
+use crate::error::Error;
+use crate::point::Coord;
 use crate::point::Point;
 use crate::point::Vec8;
 use bitflags::bitflags;
@@ -8,6 +10,7 @@ use nalgebra::SMatrix;
 use nalgebra::matrix;
 use rand::distr::StandardUniform;
 use rand::prelude::*;
+use rand::rngs::SmallRng;
 use std::ops::Mul;
 use std::str::FromStr;
 
@@ -65,8 +68,8 @@ impl Mirror {
 }
 
 impl FromStr for Mirror {
-    type Err = ();
-    fn from_str(st: &str) -> Result<Self, ()> {
+    type Err = Error;
+    fn from_str(st: &str) -> Result<Self, Error> {
         match st {
             "A0" => Ok(Mirror::A0),
             "A1" => Ok(Mirror::A1),
@@ -76,14 +79,14 @@ impl FromStr for Mirror {
             "B1" => Ok(Mirror::B1),
             "C" => Ok(Mirror::C),
             "M" => Ok(Mirror::M),
-            _ => Err(()),
+            _ => Err(Error::ParseMirror(st.to_owned())),
         }
     }
 }
 
 /// Matrix in E8 group times 4
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct E8(SMatrix<i16, 8, 8>);
+pub struct E8(SMatrix<Coord, 8, 8>);
 
 impl E8 {
     pub fn identity() -> Self {
@@ -98,17 +101,44 @@ impl E8 {
 impl Mul<E8> for E8 {
     type Output = E8;
     fn mul(self, other: E8) -> E8 {
-        E8(self.0 * other.0 / 4)
+        let prod = self.0 * other.0;
+        debug_assert!(prod.iter().all(|x| x % 4 == 0), "inexact E8 product {prod}");
+        E8(prod / 4)
     }
 }
 
 impl Mul<E8> for Point {
     type Output = Point;
     fn mul(self, other: E8) -> Point {
-        Point::new(self.vec() * other.0 / 4)
+        let prod = self.vec() * other.0;
+        debug_assert!(prod.iter().all(|x| x % 4 == 0), "inexact E8 image {prod}");
+        Point::new(prod / 4)
     }
 }
 
+/// Vertices of the polygon swept out from `vertex` by applying the two `generators` in turn,
+/// in cyclic order, where the product of the generators has order `order`
+///
+/// With the reflections of two mirrors of linking number `order` this is the 2-face of a
+/// Wythoffian polytope around `vertex`. A reflection that fixes the current vertex adds no new
+/// one, so a pair with only one ringed mirror gives a polygon of `order` sides instead of
+/// `2 * order`.
+pub fn polygon(vertex: Point, generators: [E8; 2], order: u32) -> Vec<Point> {
+    let mut vertices = vec![vertex];
+    let mut word = E8::identity();
+    for step in 1..2 * order as usize {
+        word = generators[(step + 1) % 2] * word;
+        let next = vertex * word;
+        if vertices.last() != Some(&next) {
+            vertices.push(next);
+        }
+    }
+    while vertices.len() > 1 && vertices.last() == Some(&vertex) {
+        vertices.pop();
+    }
+    vertices
+}
+
 impl Distribution<E8> for StandardUniform {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> E8 {
         MirrorSet::all().sample(rng)
@@ -370,7 +400,8 @@ impl MirrorSet {
         let mut seen_vertices = 0;
         let mut orbits = FxHashSet::from_iter([]);
         let mut points = Vec::new();
-        let mut rng = rand::rng();
+        // seeded so that the E8s, and so everything written from them, are the same every run
+        let mut rng = SmallRng::seed_from_u64(self.bits().into());
         loop {
             let e8: E8 = rng.random();
             let point = vertex * e8;
@@ -422,6 +453,7 @@ impl Distribution<E8> for MirrorSet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::prop;
 
     #[test]
     fn all_sizes_divisible() {
@@ -465,4 +497,84 @@ mod tests {
             )
         }
     }
+
+    fn random_word(rng: &mut impl Rng) -> E8 {
+        (0..rng.random_range(0..30))
+            .map(|_| Mirror::ALL[rng.random_range(0..8)].mat())
+            .fold(E8::identity(), |x, y| x * y)
+    }
+
+    #[test]
+    fn prop_e8_inv() {
+        prop::check(500, |rng| {
+            let e8 = random_word(rng);
+            assert_eq!(e8 * e8.inv(), E8::identity());
+            assert_eq!(e8.inv() * e8, E8::identity());
+        });
+    }
+
+    #[test]
+    fn mirror_mat_involution() {
+        for mirror in Mirror::ALL {
+            assert_eq!(mirror.mat() * mirror.mat(), E8::identity(), "{mirror:?}");
+        }
+    }
+
+    #[test]
+    fn prop_mirror_mat_preserves_lattice() {
+        for mirror in Mirror::ALL {
+            assert!(prop::in_lattice(mirror.pole()), "{mirror:?}");
+        }
+        prop::check(500, |rng| {
+            let v = prop::lattice_vec(rng, 12);
+            assert!(prop::in_lattice(v), "{v}");
+            for mirror in Mirror::ALL {
+                let image = v * mirror.mat().0;
+                assert!(image.iter().all(|x| x % 4 == 0), "{mirror:?} {v}");
+                let image = image / 4;
+                assert!(prop::in_lattice(image), "{mirror:?} {v} -> {image}");
+                assert_eq!(image.dot(&image), v.dot(&v));
+            }
+        });
+    }
+
+    #[test]
+    fn prop_point_mul_e8() {
+        prop::check(200, |rng| {
+            let v = prop::lattice_vec(rng, 8);
+            let e8 = random_word(rng);
+            let point = Point::new(v) * e8;
+            assert!(prop::in_lattice(point.vec()));
+            assert_eq!(point * e8.inv(), Point::new(v));
+        });
+    }
+
+    #[test]
+    fn polygon_cycles() {
+        let vertex = MirrorSet::all().vertex();
+        for m1 in Mirror::ALL {
+            for m2 in Mirror::ALL {
+                if m1 == m2 {
+                    continue;
+                }
+                let link = m1.link(m2) as usize;
+                for (vertex, len) in [
+                    (vertex, 2 * link),
+                    (MirrorSet::from_mirror(m1).vertex(), link),
+                    (MirrorSet::from_mirror(m2).vertex(), link),
+                ] {
+                    let vertices = polygon(vertex, [m1.mat(), m2.mat()], link as u32);
+                    assert_eq!(vertices.len(), len, "{m1:?} {m2:?}");
+                    let edge = |a: Point, b: Point| {
+                        let d = a.vec() - b.vec();
+                        d.dot(&d)
+                    };
+                    let first = edge(vertices[0], vertices[1]);
+                    for i in 0..len {
+                        assert_eq!(edge(vertices[i], vertices[(i + 1) % len]), first);
+                    }
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,88 @@
+use crate::point::Coord;
+use crate::point::Orbit;
+use crate::point::Vec8;
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Unrecognized mirror name
+    ParseMirror(String),
+    /// Unrecognized compression name
+    ParseCompression(String),
+    /// Point whose orbit, by its representative and sign, is not in the point set being indexed
+    UnknownOrbit {
+        rep: Vec8,
+        sign: Coord,
+    },
+    Io(std::io::Error),
+    /// Checkpoint that is malformed or does not match the write being resumed
+    Checkpoint(String),
+    /// Shard specification or shard file that does not fit with the others
+    Shard(String),
+    /// Output that would not fit on its filesystem or within its budget
+    Space(String),
+    /// Oriented .off whose boundary of a boundary is not zero
+    Orientation(String),
+    /// Mirror set that is not a face type of the polytope asked about
+    FaceType(String),
+    /// Coordinate system that is unknown or does not apply to the polytope
+    Coordinates(String),
+    /// Options that do not apply to the polytope or to each other
+    Options(String),
+    /// Internal assumption about the polytope that turned out false
+    Invariant(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn unknown_orbit<G>(orbit: Orbit<8, G>) -> Self {
+        Error::UnknownOrbit {
+            rep: orbit.rep,
+            sign: orbit.sign,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ParseMirror(st) => write!(
+                f,
+                "unknown mirror {st:?}, expected one of A0 A1 A2 A3 B0 B1 C M"
+            ),
+            Error::ParseCompression(st) => write!(
+                f,
+                "unknown compression {st:?}, expected one of none gzip zstd xz"
+            ),
+            Error::UnknownOrbit { rep, sign } => write!(
+                f,
+                "orbit with representative {rep} and sign {sign} is not in the point set"
+            ),
+            Error::Io(err) => write!(f, "{err}"),
+            Error::Checkpoint(msg) => write!(f, "checkpoint: {msg}"),
+            Error::Shard(msg) => write!(f, "shard: {msg}"),
+            Error::Space(msg) => write!(f, "{msg}"),
+            Error::Orientation(msg) => write!(f, "orientation: {msg}"),
+            Error::FaceType(msg) => write!(f, "{msg}"),
+            Error::Coordinates(msg) => write!(f, "{msg}"),
+            Error::Options(msg) => write!(f, "{msg}"),
+            Error::Invariant(msg) => write!(f, "invariant violated: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
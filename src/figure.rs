@@ -0,0 +1,335 @@
+//! Face figures: the polytope formed by the faces containing a given face.
+//!
+//! The faces of dimension `k + 1 + j` containing the face of type `face_type` around
+//! [`MirrorSet::vertex`] are the `j`-faces of its face figure, with the polytope itself as the
+//! whole figure. They are the images of the faces around the vertex under the mirrors that fix
+//! the face without being next to it, so the figure comes without enumerating the polytope. Its
+//! vertices are where the rays from the face's center to the centers of the `k + 1`-faces
+//! containing it meet the hyperplane at distance 1 towards the polytope's center.
+
+use crate::coords::decimal;
+use crate::e8::MirrorSet;
+use crate::error::Error;
+use crate::error::Result;
+use crate::orient::Row;
+use crate::orient::frame;
+use crate::orient::to_row;
+use crate::point::D8;
+use fxhash::FxHashMap;
+use nalgebra::DMatrix;
+use std::io::Write;
+
+/// Decimal places of the coordinates of a face figure
+const FIGURE_PRECISION: usize = 9;
+
+/// Face figure of a face of a polytope, of dimension `7 - k` for a `k`-face
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaceFigure {
+    /// Coordinates of the vertices in an orthonormal basis of the cutting hyperplane
+    pub vertices: Vec<Vec<f64>>,
+    /// Faces of every dimension up to the whole figure, by dimension with the first left empty
+    ///
+    /// Edges list their two vertices and polygons their vertices in cyclic order, like in the
+    /// .off. Higher faces list their subfaces by index.
+    pub faces: Vec<Vec<Vec<usize>>>,
+}
+
+/// Center of the face spanned by `poles` around `vertex`, its projection onto the fixed space
+/// of the face's mirrors
+fn center(vertex: Row, poles: &[Row]) -> Result<Row> {
+    if poles.is_empty() {
+        return Ok(vertex);
+    }
+    let gram = DMatrix::from_fn(poles.len(), poles.len(), |a, b| poles[a].dot(&poles[b]));
+    let dots = DMatrix::from_fn(poles.len(), 1, |a, _| poles[a].dot(&vertex));
+    let coefficients = gram
+        .lu()
+        .solve(&dots)
+        .ok_or_else(|| Error::Invariant("poles of distinct mirrors are dependent".to_owned()))?;
+    Ok(vertex
+        - poles
+            .iter()
+            .zip(coefficients.iter())
+            .map(|(pole, c)| pole * *c)
+            .sum::<Row>())
+}
+
+fn poles(mirrors: MirrorSet) -> Vec<Row> {
+    mirrors.mirrors().map(|m| to_row(m.pole())).collect()
+}
+
+impl MirrorSet {
+    /// Face figure of the faces of type `face_type`, which must be a face type of dimension
+    /// below 7
+    pub fn face_figure(self, face_type: Self) -> Result<FaceFigure> {
+        let k = face_type.size() as usize;
+        let face_types = self.face_types();
+        if k >= 7 || !face_types[k].contains(&face_type) {
+            return Err(Error::FaceType(format!(
+                "{face_type:?} is not a face type of {self:?} below dimension 7"
+            )));
+        }
+        let n = 7 - k;
+        // mirrors fixing the face without being next to it, which fix the vertex and the
+        // face's center
+        let around = !(self.face_center(face_type) | face_type);
+
+        // elements by dimension in the figure, as centers, types and words taking them there
+        let mut elements = Vec::new();
+        let mut indices = Vec::new();
+        for j in 0..=n {
+            let mut dim_elements = Vec::new();
+            let mut dim_indices = FxHashMap::default();
+            for &element_type in &face_types[k + 1 + j] {
+                if !element_type.contains(face_type) {
+                    continue;
+                }
+                let center = self.face_center(element_type).vertex();
+                for (point, word) in Self::orbit_words(center, around).iter_words() {
+                    dim_indices.insert(point, dim_elements.len());
+                    dim_elements.push((point, element_type, word));
+                }
+            }
+            elements.push(dim_elements);
+            indices.push(dim_indices);
+        }
+
+        let mut faces = vec![Vec::new()];
+        for j in 1..=n {
+            let mut dim_faces = Vec::new();
+            for &(_, element_type, word) in &elements[j] {
+                // the subfaces containing the face, by the same argument inside the element
+                let mut subfaces = Vec::new();
+                for &subface_type in &face_types[k + j] {
+                    if !subface_type.contains(face_type) || !element_type.contains(subface_type) {
+                        continue;
+                    }
+                    let center = self.face_center(subface_type).vertex();
+                    for subface in Self::orbit_under(center, element_type & around).iter() {
+                        let index = indices[j - 1].get(&(subface * word)).ok_or_else(|| {
+                            Error::Invariant(format!(
+                                "subface of a face containing {face_type:?} does not contain it"
+                            ))
+                        })?;
+                        subfaces.push(*index);
+                    }
+                }
+                subfaces.sort_unstable();
+                dim_faces.push(subfaces);
+            }
+            faces.push(dim_faces);
+        }
+        if n >= 2 {
+            faces[2] = faces[2]
+                .iter()
+                .map(|edges| polygon_cycle(edges, &faces[1]))
+                .collect::<Result<_>>()?;
+        }
+
+        let vertex = to_row(self.vertex().vec());
+        let face_center = center(vertex, &poles(face_type))?;
+        let inward = -face_center.normalize();
+        let mut basis: Vec<Row> = Vec::new();
+        let candidates = poles(face_type)
+            .into_iter()
+            .chain([inward])
+            .chain((0..8).map(|i| Row::from_fn(|_, j| if i == j { 1.0 } else { 0.0 })));
+        for candidate in candidates {
+            let rest = basis
+                .iter()
+                .fold(candidate, |rest, b| rest - b * rest.dot(b));
+            if rest.norm() > 1e-6 {
+                basis.push(rest.normalize());
+            }
+        }
+        let vertices = elements[0]
+            .iter()
+            .map(|&(_, element_type, word)| {
+                let ray = center(vertex, &poles(element_type))? * frame(word, D8::identity())
+                    - face_center;
+                let cut = ray / ray.dot(&inward);
+                Ok(basis[k + 1..].iter().map(|b| cut.dot(b)).collect())
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(FaceFigure { vertices, faces })
+    }
+}
+
+/// Vertices of the polygon bounded by `edges`, indices into `all_edges`, in cyclic order
+fn polygon_cycle(edges: &[usize], all_edges: &[Vec<usize>]) -> Result<Vec<usize>> {
+    let bad = || Error::Invariant(format!("figure edges {edges:?} do not form a cycle"));
+    let mut cycle = all_edges[*edges.first().ok_or_else(bad)?].clone();
+    let mut previous = edges[0];
+    while cycle.len() < edges.len() {
+        let last = *cycle.last().ok_or_else(bad)?;
+        let &next = edges
+            .iter()
+            .find(|&&edge| edge != previous && all_edges[edge].contains(&last))
+            .ok_or_else(bad)?;
+        let &other = all_edges[next]
+            .iter()
+            .find(|&&vertex| vertex != last)
+            .ok_or_else(bad)?;
+        cycle.push(other);
+        previous = next;
+    }
+    Ok(cycle)
+}
+
+impl FaceFigure {
+    pub fn dim(&self) -> usize {
+        self.faces.len() - 1
+    }
+
+    /// Number of faces of each dimension, starting with the vertices
+    pub fn f_vector(&self) -> Vec<usize> {
+        [self.vertices.len()]
+            .into_iter()
+            .chain(self.faces[1..].iter().map(Vec::len))
+            .collect()
+    }
+
+    /// Write the figure as an .off laid out like the polytope's, with the whole figure last
+    pub fn write_off(&self, mut writer: impl Write) -> Result<()> {
+        let n = self.dim();
+        let f_vector = self.f_vector();
+        // the counts of the j-faces for j < n, with the faces before the edges
+        let counts = match n {
+            1 => vec![f_vector[0]],
+            2 => vec![f_vector[0], f_vector[1]],
+            _ => [f_vector[0], f_vector[2], f_vector[1]]
+                .into_iter()
+                .chain(f_vector[3..n].iter().copied())
+                .collect(),
+        };
+        writeln!(writer, "{n}OFF")?;
+        for count in counts {
+            write!(writer, "{count} ")?;
+        }
+        write!(writer, "\n\n# Vertices\n")?;
+        for vertex in &self.vertices {
+            let coordinates: Vec<_> = vertex
+                .iter()
+                .map(|&x| decimal(x, FIGURE_PRECISION))
+                .collect();
+            writeln!(writer, "{}", coordinates.join(" "))?;
+        }
+        for (j, faces) in self.faces.iter().enumerate().skip(2) {
+            if j == 2 {
+                write!(writer, "\n# Faces\n")?;
+            } else {
+                write!(writer, "\n# {j}-faces\n")?;
+            }
+            for face in faces {
+                write!(writer, "{}", face.len())?;
+                for index in face {
+                    write!(writer, " {index}")?;
+                }
+                writeln!(writer)?;
+            }
+        }
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn face_figures_of_4_21() {
+        let mirrors = MirrorSet::A0;
+        // vertex figure 3_21 and edge figure 2_21
+        let vertex_figure = mirrors.face_figure(MirrorSet::empty()).unwrap();
+        assert_eq!(
+            vertex_figure.f_vector(),
+            [56, 756, 4032, 10080, 12096, 6048, 702, 1]
+        );
+        let edge_figure = mirrors.face_figure(MirrorSet::A0).unwrap();
+        assert_eq!(edge_figure.f_vector(), [27, 216, 720, 1080, 648, 99, 1]);
+
+        // the vertices of the vertex figure are the 56 neighbours, all equally far apart
+        let distances: Vec<f64> = vertex_figure
+            .vertices
+            .iter()
+            .map(|v| {
+                v.iter()
+                    .zip(&vertex_figure.vertices[0])
+                    .map(|(a, b)| (a - b).powi(2))
+                    .sum()
+            })
+            .filter(|&d: &f64| d > 1e-9)
+            .collect();
+        let nearest = distances.iter().copied().fold(f64::INFINITY, f64::min);
+        assert_eq!(
+            distances
+                .iter()
+                .filter(|&&d| (d - nearest).abs() < 1e-9)
+                .count(),
+            27
+        );
+        assert!(mirrors.face_figure(MirrorSet::A1).is_err());
+
+        // the omnitruncate's vertex figure is a simplex
+        let simplex = MirrorSet::all().face_figure(MirrorSet::empty()).unwrap();
+        assert_eq!(simplex.f_vector(), [8, 28, 56, 70, 56, 28, 8, 1]);
+    }
+
+    #[test]
+    fn face_figures_euler() {
+        for mirrors in [
+            MirrorSet::A0 | MirrorSet::M,
+            MirrorSet::B1 | MirrorSet::A3,
+            MirrorSet::C | MirrorSet::A1 | MirrorSet::B0,
+            MirrorSet::all() - MirrorSet::A2,
+        ] {
+            let face_types = mirrors.face_types();
+            for (k, k_face_types) in face_types.iter().enumerate().take(7) {
+                for &face_type in k_face_types {
+                    let figure = mirrors.face_figure(face_type).unwrap();
+                    let n = figure.dim();
+                    assert_eq!(n, 7 - k);
+                    let f_vector = figure.f_vector();
+                    assert_eq!(f_vector[n], 1);
+                    // Euler characteristic of the boundary of an n-polytope
+                    let euler: i64 = (0..n)
+                        .map(|j| if j % 2 == 0 { 1 } else { -1 } * f_vector[j] as i64)
+                        .sum();
+                    assert_eq!(
+                        euler,
+                        1 - (-1i64).pow(n as u32),
+                        "{mirrors:?} {face_type:?}"
+                    );
+                    let mut off = Vec::new();
+                    figure.write_off(&mut off).unwrap();
+                    assert!(off.starts_with(format!("{n}OFF\n").as_bytes()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn low_dimensional_headers() {
+        let mirrors = MirrorSet::A0 | MirrorSet::M;
+        let face_types = mirrors.face_types();
+        for k in [5, 6] {
+            for &face_type in &face_types[k] {
+                let figure = mirrors.face_figure(face_type).unwrap();
+                let mut off = Vec::new();
+                figure.write_off(&mut off).unwrap();
+                let off = String::from_utf8(off).unwrap();
+                let header = if k == 5 {
+                    // a polygon, with as many edges as vertices
+                    let sides = figure.f_vector()[1];
+                    format!("2OFF\n{sides} {sides} \n")
+                } else {
+                    // a segment
+                    "1OFF\n2 \n".to_owned()
+                };
+                assert!(off.starts_with(&header), "{face_type:?}: {off}");
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Golden-file regression tests for small polytopes.
+//!
+//! Each polytope is streamed through a [`Summary`] section by section: how many lines have each
+//! subface count, and the SHA-256 of the section's text. Output is canonical, since the orbit
+//! `E8`s are seeded and the subfaces on every line are sorted, so any change to a summary is a
+//! change to the .off. The summaries are checked in under `golden/`, and
+//! `E8_BLESS=1 cargo test golden` rewrites them after an intended change.
+
+use crate::e8::MirrorSet;
+use crate::off::OffOptions;
+use crate::progress::CountingWriter;
+use crate::progress::ProgressMode;
+use sha2::Digest;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Summary of an .off written into it a line at a time, without keeping the .off: its header
+/// and, for every section, the number of lines with each count of vertices or subfaces and the
+/// SHA-256 of the section
+#[derive(Default)]
+struct Summary {
+    /// The unfinished last line
+    line: Vec<u8>,
+    header: Vec<String>,
+    /// Finished sections, with their name and number of lines
+    sections: Vec<(String, u64)>,
+    text: String,
+    /// Name, line counts by subface count and hash of the current section
+    section: Option<(String, BTreeMap<u64, u64>, Sha256)>,
+}
+
+impl Summary {
+    fn push_line(&mut self, line: &str) {
+        if let Some(name) = line.strip_prefix("# ") {
+            self.finish_section();
+            self.section = Some((name.to_owned(), BTreeMap::new(), Sha256::new()));
+            return;
+        }
+        let Some((name, counts, hash)) = &mut self.section else {
+            if !line.is_empty() {
+                self.header.push(line.trim_end().to_owned());
+            }
+            return;
+        };
+        hash.update(line);
+        hash.update("\n");
+        if line.is_empty() {
+            return;
+        }
+        let count = if name == "Vertices" {
+            line.split(' ').count() as u64
+        } else {
+            line.split(' ').next().unwrap().parse().unwrap()
+        };
+        *counts.entry(count).or_insert(0) += 1;
+    }
+
+    fn finish_section(&mut self) {
+        if let Some((name, counts, hash)) = self.section.take() {
+            write!(self.text, "{name}").unwrap();
+            for (count, lines) in &counts {
+                write!(self.text, " {count}:{lines}").unwrap();
+            }
+            writeln!(self.text, " sha256 {:x}", hash.finalize()).unwrap();
+            self.sections.push((name, counts.values().sum()));
+        }
+    }
+
+    /// The summary's text, and the number of lines of every section
+    fn finish(mut self) -> (String, Vec<(String, u64)>) {
+        if !self.line.is_empty() {
+            let line = String::from_utf8(std::mem::take(&mut self.line)).unwrap();
+            self.push_line(&line);
+        }
+        self.finish_section();
+        let header = format!("header {}\n", self.header.join(" "));
+        (header + &self.text, self.sections)
+    }
+}
+
+impl Write for Summary {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut lines = buf.split(|&byte| byte == b'\n');
+        let last = lines.next_back().unwrap_or_default();
+        for part in lines {
+            self.line.extend_from_slice(part);
+            let line = String::from_utf8(std::mem::take(&mut self.line)).unwrap();
+            self.push_line(&line);
+        }
+        self.line.extend_from_slice(last);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Write `mirrors` up to `dim_limit`, check its size, count the faces of every dimension it
+/// writes against `f_vector`, and compare its summary with `golden/{name}.txt`
+fn check_golden(name: &str, mirrors: MirrorSet, dim_limit: usize, f_vector: [u64; 8]) {
+    let options = OffOptions {
+        dim_limit: Some(dim_limit),
+        progress: ProgressMode::Quiet,
+        ..Default::default()
+    };
+    let mut summary = Summary::default();
+    let mut off = CountingWriter::new(&mut summary);
+    mirrors.write_off(&mut off, &options).unwrap();
+    assert_eq!(
+        off.count(),
+        mirrors.off_size(Some(dim_limit)).unwrap(),
+        "{name}"
+    );
+    let (summary, sections) = summary.finish();
+
+    // the sections are the vertices and the faces of dimension 2 up to `dim_limit`, and the
+    // edges, which an .off leaves out, are only counted in the header
+    let counted: Vec<_> = sections.iter().map(|&(_, lines)| lines).collect();
+    let mut expected = vec![f_vector[0]];
+    expected.extend((2..=dim_limit).map(|k| f_vector.get(k).copied().unwrap_or(1)));
+    assert_eq!(counted, expected, "{name}");
+    assert!(
+        summary.starts_with(&format!(
+            "header 8OFF {} {} {} ",
+            f_vector[0], f_vector[2], f_vector[1]
+        )),
+        "{name}"
+    );
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("golden/{name}.txt"));
+    if std::env::var_os("E8_BLESS").is_some() {
+        std::fs::write(&path, &summary).unwrap();
+    }
+    let golden = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("{}: {err}, run with E8_BLESS=1", path.display()));
+    assert!(
+        summary == golden,
+        "{name} differs from {}:\n{summary}",
+        path.display()
+    );
+}
+
+#[test]
+fn golden_4_21() {
+    let f_vector = [240, 6720, 60480, 241920, 483840, 483840, 207360, 19440];
+    check_golden("4_21", MirrorSet::A0, 8, f_vector);
+}
+
+#[test]
+fn golden_2_41() {
+    let f_vector = [2160, 69120, 483840, 1209600, 1209600, 544320, 144960, 17520];
+    check_golden("2_41", MirrorSet::B0, 8, f_vector);
+}
+
+#[test]
+fn golden_1_42() {
+    let f_vector = [
+        17280, 483840, 2419200, 3628800, 2298240, 725760, 106080, 2400,
+    ];
+    check_golden("1_42", MirrorSet::C, 8, f_vector);
+}
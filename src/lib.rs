@@ -0,0 +1,69 @@
+//! Wythoffian E8 polytopes and their .off files.
+//!
+//! A polytope is given by the [`MirrorSet`] of its ringed mirrors. Its elements are enumerated
+//! as D8 orbits of [`Point`]s, collected into [`PointSet`]s that give every element a global
+//! index, and written out with [`MirrorSet::write_off`].
+
+mod checkpoint;
+mod combs;
+mod compress;
+mod coords;
+mod e8;
+mod error;
+mod figure;
+#[cfg(test)]
+mod golden;
+mod metric;
+mod off;
+mod orient;
+mod point;
+mod progress;
+#[cfg(test)]
+mod prop;
+mod shard;
+mod space;
+mod volume;
+
+pub use checkpoint::Checkpoint;
+pub use compress::CompressWriter;
+pub use compress::Compression;
+pub use coords::Coordinates;
+pub use e8::E8;
+pub use e8::Mirror;
+pub use e8::MirrorSet;
+pub use e8::Ring;
+pub use error::Error;
+pub use error::Result;
+pub use figure::FaceFigure;
+pub use metric::DihedralAngle;
+pub use metric::FacetDistance;
+pub use metric::Metrics;
+pub use metric::Surd;
+pub use metric::SurdSum;
+pub use off::OffLayout;
+pub use off::OffOptions;
+pub use off::OffPosition;
+pub use off::PointSet;
+pub use orient::check_boundaries;
+pub use point::AxSign;
+pub use point::B8;
+pub use point::Bn;
+pub use point::Coord;
+pub use point::D8;
+pub use point::Dn;
+pub use point::Orbit;
+pub use point::OrbitTable;
+pub use point::PackedPoint;
+pub use point::Point;
+pub use point::S8;
+pub use point::Sn;
+pub use point::Symmetry;
+pub use point::Vec8;
+pub use point::VecN;
+pub use progress::ProgressMode;
+pub use shard::Shard;
+pub use shard::merge_shards;
+pub use space::check_space;
+pub use space::parse_size;
+pub use volume::FaceVolume;
+pub use volume::Volumes;
@@ -1,30 +1,114 @@
-#![allow(dead_code)]
-use crate::e8::Mirror;
-use crate::e8::MirrorSet;
-use crate::e8::Ring::XX;
+use clap::ArgGroup;
 use clap::Parser;
+use clap::builder::RangedU64ValueParser;
+use e8_omni_off::Checkpoint;
+use e8_omni_off::Compression;
+use e8_omni_off::Coordinates;
+use e8_omni_off::Error;
+use e8_omni_off::Mirror;
+use e8_omni_off::MirrorSet;
+use e8_omni_off::OffLayout;
+use e8_omni_off::OffOptions;
+use e8_omni_off::ProgressMode;
+use e8_omni_off::Ring::XX;
+use e8_omni_off::Shard;
+use e8_omni_off::Surd;
+use e8_omni_off::SurdSum;
+use e8_omni_off::check_space;
+use e8_omni_off::parse_size;
 use std::fs::File;
+use std::fs::OpenOptions;
 use std::io::BufWriter;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
-mod combs;
-mod e8;
-mod off;
-mod point;
-
-fn print_size(size: u64) {
+/// `size` in bytes rounded down to MB, GB or TB
+fn human_size(size: u64) -> String {
     if size < 10_000_000_000 {
-        println!("Estimated size: {} MB", size / 1_000_000);
+        format!("{} MB", size / 1_000_000)
     } else if size < 10_000_000_000_000 {
-        println!("Estimated size: {} GB", size / 1_000_000_000);
+        format!("{} GB", size / 1_000_000_000)
     } else {
-        println!("Estimated size: {} TB", size / 1_000_000_000_000);
+        format!("{} TB", size / 1_000_000_000_000)
+    }
+}
+
+/// Print the size of the .off of `layout` and, with `compression`, its estimated compressed size,
+/// and return the estimated compression ratio
+fn print_sizes(layout: &OffLayout, compression: Compression) -> Result<f64, Error> {
+    let size = layout.bytes();
+    println!("Size: {size} bytes ({})", human_size(size));
+    let ratio = layout.compression_ratio(compression)?;
+    if compression != Compression::None {
+        println!(
+            "Estimated {compression} size: {}",
+            human_size((size as f64 * ratio) as u64)
+        );
+    }
+    Ok(ratio)
+}
+
+fn print_metrics(
+    mirror_set: MirrorSet,
+    coordinates: Coordinates,
+    precision: usize,
+) -> Result<(), Error> {
+    let metrics = mirror_set.metrics(coordinates)?;
+    let decimal = |surd: Surd| format!("{surd} = {:.precision$}", surd.to_f64());
+    println!("Edge length: {}", decimal(metrics.edge_length));
+    println!("Circumradius: {}", decimal(metrics.circumradius));
+    for facet in metrics.facet_distances {
+        println!(
+            "Facet {:?} distance: {}",
+            facet.face_type,
+            decimal(facet.distance)
+        );
     }
+    for angle in metrics.dihedral_angles {
+        let [a, b] = angle.facet_types;
+        println!(
+            "Dihedral angle {a:?} to {b:?}: arccos({}) = {:.precision$}°",
+            angle.cos,
+            angle.degrees()
+        );
+    }
+    Ok(())
+}
+
+fn print_volumes(
+    mirror_set: MirrorSet,
+    coordinates: Coordinates,
+    precision: usize,
+) -> Result<(), Error> {
+    let volumes = mirror_set.volumes(coordinates)?;
+    let decimal = |sum: &SurdSum| format!("{sum} = {:.precision$}", sum.to_f64());
+    println!("Volume: {}", decimal(&volumes.volume));
+    for facet in &volumes.facets {
+        println!(
+            "Facet {:?} volume, {} of them: {}",
+            facet.face_type,
+            facet.count,
+            decimal(&facet.volume)
+        );
+    }
+    println!("Surface volume: {}", decimal(&volumes.surface));
+    Ok(())
 }
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
+#[command(group(
+    ArgGroup::new("action")
+        .args(["single_vertex", "vertices", "off_size", "metrics", "volumes", "off"]),
+))]
+#[command(group(
+    ArgGroup::new("output")
+        .args(["off_size", "metrics", "volumes", "off"])
+        .multiple(true),
+))]
 struct Cli {
     /// Mirrors
     mirrors: Vec<String>,
@@ -37,26 +121,99 @@ struct Cli {
     #[arg(short, long)]
     vertices: bool,
 
-    /// Estimated size of .off
+    /// Exact size of .off, and estimated size once compressed
     #[arg(long)]
     off_size: bool,
 
+    /// Edge length, circumradius, facet distances and dihedral angles, exactly and in decimals
+    #[arg(long)]
+    metrics: bool,
+
+    /// Exact 8-volume, 7-volume of each facet and of the whole boundary
+    #[arg(long)]
+    volumes: bool,
+
     /// Write .off
     #[arg(short, long, value_name = "FILE")]
     off: Option<PathBuf>,
 
     /// Dimension limit
-    #[arg(long)]
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(0..=8))]
     dim_limit: Option<usize>,
+
+    /// No progress on stderr
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Progress on stderr as JSON lines
+    #[arg(long, conflicts_with = "quiet")]
+    progress_json: bool,
+
+    /// Continue an interrupted write from its checkpoint
+    #[arg(long, requires = "off")]
+    resume: bool,
+
+    /// Write only shard i of n, see merge_shards
+    #[arg(long, value_name = "i/n", requires = "off")]
+    shard: Option<Shard>,
+
+    /// Compress the .off with none, gzip, zstd or xz, by default from its extension
+    #[arg(long, value_name = "COMPRESSION")]
+    compress: Option<Compression>,
+
+    /// Refuse to write a .off larger than SIZE, like 500G
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, requires = "off")]
+    max_size: Option<u64>,
+
+    /// Write the .off even if it would not fit on disk or within --max-size
+    #[arg(long, requires = "off")]
+    force: bool,
+
+    /// Orient every face, marking reversed subfaces with a minus sign, see check_boundaries
+    #[arg(long, requires = "off")]
+    oriented: bool,
+
+    /// Coordinates of the vertices: lattice, even or odd for Conway and Sloane's systems, dynkin
+    /// for Dynkin labels, or unit or circumradius for decimals with edge length or circumradius 1
+    #[arg(long, value_name = "SYSTEM", default_value_t = Coordinates::Lattice, requires = "output")]
+    coordinates: Coordinates,
+
+    /// Decimal places of --metrics and --volumes, and of unit or circumradius coordinates
+    #[arg(long, value_name = "PLACES", requires = "output")]
+    precision: Option<usize>,
+
+    /// Write the face figure of the faces of type MIRRORS, like A0,A1, or the vertex figure if
+    /// empty, to the .off or stdout
+    #[arg(
+        long,
+        value_name = "MIRRORS",
+        value_delimiter = ',',
+        num_args = 0..=1,
+        require_equals = true,
+        conflicts_with_all = [
+            "single_vertex", "vertices", "off_size", "metrics", "volumes", "dim_limit",
+            "progress_json", "resume", "shard", "max_size", "force", "oriented", "coordinates",
+            "precision",
+        ],
+    )]
+    face_figure: Option<Vec<Mirror>>,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+fn run(cli: Cli) -> Result<(), Error> {
     let mut mirror_set = MirrorSet::empty();
     for mirror in cli.mirrors {
-        let mirror = mirror.parse().unwrap();
+        let mirror = mirror.parse()?;
         mirror_set.set_mirror(mirror, XX);
     }
+    // lengths are printed in decimals whatever the system, but the .off only has decimals to
+    // round in unit or circumradius coordinates
+    let coordinates = match cli.precision {
+        Some(precision) if cli.off_size || cli.off.is_some() => {
+            cli.coordinates.with_precision(precision)?
+        }
+        _ => cli.coordinates,
+    };
+    let precision = cli.precision.unwrap_or(coordinates.precision());
 
     if cli.single_vertex {
         let vertex = mirror_set.vertex();
@@ -71,16 +228,198 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("{:?}", arr)
         }
     } else if cli.off_size {
-        let size = mirror_set.off_size_estimate();
-        print_size(size);
-    } else if let Some(file) = cli.off {
-        let size = mirror_set.off_size_estimate();
-        print_size(size);
-        let mut writer = BufWriter::new(File::create(file)?);
-        mirror_set.write_off(&mut writer, cli.dim_limit)?;
-        writer.flush()?;
+        let options = OffOptions {
+            dim_limit: cli.dim_limit,
+            coordinates,
+            ..Default::default()
+        };
+        print_sizes(
+            &mirror_set.off_layout(&options)?,
+            cli.compress.unwrap_or_default(),
+        )?;
+    } else if cli.metrics {
+        print_metrics(mirror_set, coordinates, precision)?;
+    } else if cli.volumes {
+        print_volumes(mirror_set, coordinates, precision)?;
+    } else if let Some(face) = cli.face_figure {
+        let face_type = face
+            .into_iter()
+            .fold(MirrorSet::empty(), |face_type, mirror| {
+                face_type | MirrorSet::from_mirror(mirror)
+            });
+        let figure = mirror_set.face_figure(face_type)?;
+        match cli.off {
+            Some(path) => {
+                let compression = cli
+                    .compress
+                    .unwrap_or_else(|| Compression::from_path(&path));
+                let mut writer = compression.writer(BufWriter::new(File::create(&path)?))?;
+                figure.write_off(&mut writer)?;
+                writer.finish()?.flush()?;
+            }
+            None => figure.write_off(std::io::stdout().lock())?,
+        }
+    } else if let Some(path) = cli.off {
+        let compression = cli
+            .compress
+            .unwrap_or_else(|| Compression::from_path(&path));
+        let checkpoint_path = Checkpoint::path_for(&path);
+        if cli.resume && compression != Compression::None {
+            return Err(Error::Checkpoint(format!(
+                "{compression} output cannot be resumed"
+            )));
+        }
+        let resume = if cli.resume {
+            Some(Checkpoint::load(&checkpoint_path)?)
+        } else {
+            None
+        };
+        let options = OffOptions {
+            dim_limit: cli.dim_limit,
+            progress: if cli.quiet {
+                ProgressMode::Quiet
+            } else if cli.progress_json {
+                ProgressMode::Json
+            } else {
+                ProgressMode::Human
+            },
+            // offsets into a compressed stream cannot be resumed from
+            checkpoint: (compression == Compression::None).then_some(checkpoint_path),
+            resume,
+            shard: cli.shard,
+            oriented: cli.oriented,
+            coordinates,
+        };
+
+        let layout = mirror_set.off_layout(&options)?;
+        let ratio = print_sizes(&layout, compression)?;
+        let size = layout.output_size(ratio)?;
+        let kept = resume.map_or(0, |checkpoint| checkpoint.bytes);
+        match check_space(&path, size, kept, cli.max_size) {
+            Err(Error::Space(msg)) if cli.force => eprintln!("warning: {msg}"),
+            Err(Error::Space(msg)) => {
+                return Err(Error::Space(format!("{msg}, use --force to write anyway")));
+            }
+            result => result?,
+        }
+
+        let file = if let Some(checkpoint) = resume {
+            let mut file = OpenOptions::new().write(true).open(&path)?;
+            if file.metadata()?.len() < checkpoint.bytes {
+                return Err(Error::Checkpoint(format!(
+                    "{} is shorter than its checkpoint",
+                    path.display()
+                )));
+            }
+            // drop whatever was written after the checkpoint
+            file.set_len(checkpoint.bytes)?;
+            file.seek(SeekFrom::End(0))?;
+            file
+        } else {
+            File::create(&path)?
+        };
+        let mut writer = compression.writer(BufWriter::new(file))?;
+        layout.write_off(&mut writer)?;
+        writer.finish()?.flush()?;
     } else {
         println!("what");
     }
     Ok(())
 }
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_args(args: &[&str]) -> Result<(), Error> {
+        let cli = Cli::try_parse_from(["e8-omni-off"].iter().chain(args))
+            .map_err(|err| Error::Options(err.to_string()))?;
+        run(cli)
+    }
+
+    #[test]
+    fn one_action() {
+        let path = std::env::temp_dir().join("e8-omni-off-action-test.off");
+        let path = path.to_str().unwrap();
+        for args in [
+            &["A0", "--off-size", "-o", path, "--oriented"][..],
+            &["A0", "--metrics", "--volumes"],
+            &["A0", "-s", "-v"],
+            &["A0", "-v", "--face-figure"],
+        ] {
+            assert!(matches!(run_args(args), Err(Error::Options(_))));
+        }
+        assert!(!std::path::Path::new(path).exists());
+    }
+
+    #[test]
+    fn coordinates_need_an_output() {
+        assert!(matches!(
+            run_args(&["A0", "-v", "--coordinates", "unit"]),
+            Err(Error::Options(_))
+        ));
+        run_args(&[
+            "A0",
+            "--off-size",
+            "--coordinates",
+            "unit",
+            "--precision",
+            "3",
+        ])
+        .unwrap();
+    }
+
+    #[test]
+    fn precision_of_printed_decimals() {
+        run_args(&["A0", "--metrics", "--precision", "4"]).unwrap();
+        run_args(&["A0", "--volumes", "--precision", "4"]).unwrap();
+        run_args(&[
+            "A0",
+            "--metrics",
+            "--coordinates",
+            "even",
+            "--precision",
+            "4",
+        ])
+        .unwrap();
+        run_args(&[
+            "A0",
+            "--metrics",
+            "--coordinates",
+            "unit",
+            "--precision",
+            "4",
+        ])
+        .unwrap();
+    }
+
+    #[test]
+    fn precision_without_decimals() {
+        // nothing would be printed in decimals
+        assert!(matches!(
+            run_args(&["A0", "--precision", "4"]),
+            Err(Error::Options(_))
+        ));
+        assert!(matches!(
+            run_args(&["A0", "--vertices", "--precision", "4"]),
+            Err(Error::Options(_))
+        ));
+        // the lattice coordinates of the .off are integers
+        let path = std::env::temp_dir().join("e8-omni-off-precision-test.off");
+        assert!(matches!(
+            run_args(&["A0", "-o", path.to_str().unwrap(), "--precision", "4"]),
+            Err(Error::Coordinates(_))
+        ));
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,441 @@
+//! Metric properties of a polytope: its edge length, circumradius, the distances from its center
+//! to the hyperplanes of its facets and the dihedral angles between adjacent facets.
+//!
+//! Every length squared is rational in lattice coordinates, since [`MirrorSet::vertex`] and the
+//! poles are lattice vectors, so the lengths are exact [`Surd`]s. The hyperplane of the facet of
+//! type `face_type` around the vertex is orthogonal to the vertex of its
+//! [`face_center`](MirrorSet::face_center), which is orthogonal to every pole of the facet.
+
+use crate::coords::Coordinates;
+use crate::e8::MirrorSet;
+use crate::error::Error;
+use crate::error::Result;
+use crate::point::Point;
+use crate::point::Vec8;
+use nalgebra::RowSVector;
+use std::fmt::Display;
+use std::iter::Sum;
+use std::ops::Add;
+use std::ops::Mul;
+
+pub(crate) type Vec8i = RowSVector<i64, 8>;
+
+pub(crate) fn widen(vec: Vec8) -> Vec8i {
+    vec.map(i64::from)
+}
+
+pub(crate) fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Largest `root` with `root * root` dividing `n`, and the squarefree `n / (root * root)`
+fn split_square(mut n: u128) -> (u128, u128) {
+    let mut root = 1;
+    let mut p = 2;
+    while p * p <= n {
+        while n.is_multiple_of(p * p) {
+            n /= p * p;
+            root *= p;
+        }
+        p += 1;
+    }
+    (root, n)
+}
+
+/// Exact signed square root of a rational, `num√rad/den` with `rad` squarefree and `num/den` in
+/// lowest terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Surd {
+    pub num: i128,
+    pub rad: u128,
+    pub den: u128,
+}
+
+impl Surd {
+    /// `num√rad/den` for a squarefree `rad`, in lowest terms
+    fn new(num: i128, rad: u128, den: u128) -> Self {
+        if num == 0 {
+            return Self {
+                num: 0,
+                rad: 1,
+                den: 1,
+            };
+        }
+        let g = gcd(num.unsigned_abs(), den);
+        Self {
+            num: num / g as i128,
+            rad,
+            den: den / g,
+        }
+    }
+
+    /// The rational `num/den`
+    pub fn rational(num: i128, den: u128) -> Self {
+        assert!(den != 0, "fraction over 0");
+        Self::new(num, 1, den)
+    }
+
+    /// Square root of `square_num / square_den`, negated if `negative`
+    pub fn sqrt(negative: bool, square_num: u64, square_den: u64) -> Self {
+        assert!(square_den != 0, "square root of a fraction over 0");
+        if square_num == 0 {
+            return Self::rational(0, 1);
+        }
+        let (square_num, square_den) = (u128::from(square_num), u128::from(square_den));
+        let g = gcd(square_num, square_den);
+        // √(p/q) = a√b / (c√e) = a√(be) / (ce), with b and e coprime
+        let (a, b) = split_square(square_num / g);
+        let (c, e) = split_square(square_den / g);
+        let num = a as i128;
+        Self::new(if negative { -num } else { num }, b * e, c * e)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.num as f64 * (self.rad as f64).sqrt() / self.den as f64
+    }
+}
+
+impl Mul for Surd {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        // √r √s = g √(r/g s/g), which is squarefree, for g the gcd of r and s
+        let g = gcd(self.rad, other.rad);
+        Self::new(
+            self.num * other.num * g as i128,
+            (self.rad / g) * (other.rad / g),
+            self.den * other.den,
+        )
+    }
+}
+
+/// Written like `3`, `-√2`, `3√5/4` or `1/2`
+impl Display for Surd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.num < 0 { "-" } else { "" };
+        let num = self.num.unsigned_abs();
+        match (num, self.rad) {
+            (num, 1) => write!(f, "{sign}{num}")?,
+            (1, rad) => write!(f, "{sign}√{rad}")?,
+            (num, rad) => write!(f, "{sign}{num}√{rad}")?,
+        }
+        if self.den != 1 {
+            write!(f, "/{}", self.den)?;
+        }
+        Ok(())
+    }
+}
+
+/// Exact sum of [`Surd`]s, nonzero and with distinct radicands in increasing order
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SurdSum(pub Vec<Surd>);
+
+impl SurdSum {
+    pub fn to_f64(&self) -> f64 {
+        self.0.iter().map(|surd| surd.to_f64()).sum()
+    }
+}
+
+impl From<Surd> for SurdSum {
+    fn from(surd: Surd) -> Self {
+        Self::default() + surd
+    }
+}
+
+impl Add<Surd> for SurdSum {
+    type Output = Self;
+    fn add(mut self, surd: Surd) -> Self {
+        match self.0.binary_search_by_key(&surd.rad, |term| term.rad) {
+            Ok(i) => {
+                let term = self.0[i];
+                let sum = Surd::new(
+                    term.num * surd.den as i128 + surd.num * term.den as i128,
+                    surd.rad,
+                    term.den * surd.den,
+                );
+                if sum.num == 0 {
+                    self.0.remove(i);
+                } else {
+                    self.0[i] = sum;
+                }
+            }
+            Err(i) if surd.num != 0 => self.0.insert(i, surd),
+            Err(_) => {}
+        }
+        self
+    }
+}
+
+impl Add for SurdSum {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        other.0.into_iter().fold(self, |sum, surd| sum + surd)
+    }
+}
+
+impl Mul<Surd> for SurdSum {
+    type Output = Self;
+    fn mul(self, factor: Surd) -> Self {
+        self.0
+            .into_iter()
+            .fold(Self::default(), |product, surd| product + surd * factor)
+    }
+}
+
+impl Sum for SurdSum {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |sum, term| sum + term)
+    }
+}
+
+/// Written like `1/20160 + √2/630`
+impl Display for SurdSum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some((first, rest)) = self.0.split_first() else {
+            return write!(f, "0");
+        };
+        write!(f, "{first}")?;
+        for surd in rest {
+            if surd.num < 0 {
+                write!(
+                    f,
+                    " - {}",
+                    Surd {
+                        num: -surd.num,
+                        ..*surd
+                    }
+                )?;
+            } else {
+                write!(f, " + {surd}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Distance from the center to the hyperplane of the facets of one type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FacetDistance {
+    pub face_type: MirrorSet,
+    pub distance: Surd,
+}
+
+/// Dihedral angle at the ridges of one type, between the facets on either side of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DihedralAngle {
+    pub ridge_type: MirrorSet,
+    /// Types of the two facets, the same type twice if a mirror fixing the ridge swaps them
+    pub facet_types: [MirrorSet; 2],
+    /// Cosine of the interior angle
+    pub cos: Surd,
+}
+
+impl DihedralAngle {
+    pub fn degrees(&self) -> f64 {
+        self.cos.to_f64().acos().to_degrees()
+    }
+}
+
+/// Metric properties of a polytope, with lengths in some [`Coordinates`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metrics {
+    pub edge_length: Surd,
+    pub circumradius: Surd,
+    /// By facet type, in the order of [`MirrorSet::face_types`]
+    pub facet_distances: Vec<FacetDistance>,
+    /// By ridge type, in the order of [`MirrorSet::face_types`]
+    pub dihedral_angles: Vec<DihedralAngle>,
+}
+
+impl MirrorSet {
+    /// Factor, as a fraction, taking squared lengths in lattice coordinates to `coordinates`
+    pub(crate) fn length_squared_scale(self, coordinates: Coordinates) -> Result<(u64, u64)> {
+        let (length_squared, length) = match coordinates {
+            Coordinates::Lattice => return Ok((1, 1)),
+            Coordinates::Even | Coordinates::Odd => return Ok((1, 4)),
+            Coordinates::Dynkin => {
+                return Err(Error::Coordinates(
+                    "dynkin coordinates are not orthonormal, so have no lengths".to_owned(),
+                ));
+            }
+            Coordinates::Unit(_) => (self.edge_length_squared(), "edge length"),
+            Coordinates::Circumradius(_) => (self.circumradius_squared(), "circumradius"),
+        };
+        if length_squared == 0 {
+            return Err(Error::Coordinates(format!(
+                "{self:?} has no {length} to scale to 1"
+            )));
+        }
+        Ok((1, length_squared as u64))
+    }
+
+    /// Edge length, circumradius, facet distances and dihedral angles, with lengths in
+    /// `coordinates`
+    pub fn metrics(self, coordinates: Coordinates) -> Result<Metrics> {
+        let (scale_num, scale_den) = self.length_squared_scale(coordinates)?;
+        let length =
+            |num: i64, den: i64| Surd::sqrt(false, num as u64 * scale_num, den as u64 * scale_den);
+        let vertex = widen(self.vertex().vec());
+        let face_types = self.face_types();
+        let normal = |facet_type: Self| self.face_center(facet_type).vertex();
+
+        let facet_distances = face_types[7]
+            .iter()
+            .map(|&face_type| {
+                let normal = widen(normal(face_type).vec());
+                let dot = vertex.dot(&normal);
+                FacetDistance {
+                    face_type,
+                    distance: length(dot * dot, normal.dot(&normal)),
+                }
+            })
+            .collect();
+
+        let dihedral_angles = face_types[6]
+            .iter()
+            .map(|&ridge_type| {
+                let facet_types: Vec<_> = (!ridge_type)
+                    .mirrors()
+                    .map(|m| ridge_type | Self::from_mirror(m))
+                    .filter(|facet_type| face_types[7].contains(facet_type))
+                    .collect();
+                let (facet_types, normals) = match facet_types[..] {
+                    [a, b] => ([a, b], [normal(a), normal(b)]),
+                    // the other facet is the image of this one under the mirror missing from it,
+                    // which fixes the vertex and the ridge
+                    [a] => {
+                        let mirror = (!a).mirrors().next().ok_or_else(|| {
+                            Error::Invariant(format!("facet type {a:?} has every mirror"))
+                        })?;
+                        ([a, a], [normal(a), normal(a) * mirror.mat()])
+                    }
+                    _ => {
+                        return Err(Error::Invariant(format!(
+                            "ridge type {ridge_type:?} is in {} facet types",
+                            facet_types.len()
+                        )));
+                    }
+                };
+                let [a, b]: [Vec8i; 2] = normals.map(|n: Point| widen(n.vec()));
+                let dot = a.dot(&b);
+                // the normals point outwards, so the interior angle is the supplement of theirs
+                let cos = Surd::sqrt(dot > 0, (dot * dot) as u64, (a.dot(&a) * b.dot(&b)) as u64);
+                Ok(DihedralAngle {
+                    ridge_type,
+                    facet_types,
+                    cos,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Metrics {
+            edge_length: length(self.edge_length_squared(), 1),
+            circumradius: length(self.circumradius_squared(), 1),
+            facet_distances,
+            dihedral_angles,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surds() {
+        let surd = |negative, num, den| Surd::sqrt(negative, num, den).to_string();
+        assert_eq!(surd(false, 0, 5), "0");
+        assert_eq!(surd(false, 9, 4), "3/2");
+        assert_eq!(surd(true, 8, 1), "-2√2");
+        assert_eq!(surd(false, 1, 2), "√2/2");
+        assert_eq!(surd(false, 45, 32), "3√10/8");
+        assert!((Surd::sqrt(true, 45, 32).to_f64() + (45.0f64 / 32.0).sqrt()).abs() < 1e-12);
+
+        let (half, root_2, root_6) = (
+            Surd::rational(1, 2),
+            Surd::sqrt(false, 2, 1),
+            Surd::sqrt(false, 6, 1),
+        );
+        assert_eq!((root_2 * root_6).to_string(), "2√3");
+        let sum = SurdSum::from(root_2) + half + Surd::sqrt(true, 1, 2);
+        assert_eq!(sum.to_string(), "1/2 + √2/2");
+        assert_eq!((sum.clone() * root_2).to_string(), "1 + √2/2");
+        assert_eq!(
+            sum.clone() + Surd::sqrt(true, 1, 4),
+            SurdSum::from(Surd::sqrt(false, 1, 2))
+        );
+        assert_eq!((sum + Surd::rational(-1, 1)).to_string(), "-1/2 + √2/2");
+    }
+
+    #[test]
+    fn metrics_of_4_21() {
+        let metrics = MirrorSet::A0.metrics(Coordinates::Lattice).unwrap();
+        assert_eq!(metrics.edge_length.to_string(), "2√2");
+        // the roots of E8, as long as the edges
+        assert_eq!(metrics.circumradius, metrics.edge_length);
+
+        // 7-simplices and 7-orthoplexes, in the order of their types
+        let metrics = MirrorSet::A0.metrics(Coordinates::Unit(9)).unwrap();
+        let distances: Vec<_> = metrics
+            .facet_distances
+            .iter()
+            .map(|facet| (facet.face_type, facet.distance.to_string()))
+            .collect();
+        assert_eq!(
+            distances,
+            [
+                (MirrorSet::all() - MirrorSet::C, "3/4".to_owned()),
+                (MirrorSet::all() - MirrorSet::B0, "√2/2".to_owned()),
+            ]
+        );
+        let angles: Vec<_> = metrics
+            .dihedral_angles
+            .iter()
+            .map(|angle| (angle.facet_types, angle.cos.to_string()))
+            .collect();
+        assert_eq!(
+            angles,
+            [
+                ([distances[0].0, distances[1].0], "-5√2/8".to_owned()),
+                // between orthoplexes swapped by B0
+                ([distances[1].0, distances[1].0], "-3/4".to_owned()),
+            ]
+        );
+        assert!((metrics.dihedral_angles[1].degrees() - 138.59).abs() < 0.01);
+        assert!(MirrorSet::A0.metrics(Coordinates::Dynkin).is_err());
+        assert!(MirrorSet::empty().metrics(Coordinates::Unit(9)).is_err());
+    }
+
+    #[test]
+    fn facet_hyperplanes_support() {
+        for mirrors in [
+            MirrorSet::B0,
+            MirrorSet::C,
+            MirrorSet::A0 | MirrorSet::B0,
+            MirrorSet::A0 | MirrorSet::A1,
+            MirrorSet::A0 | MirrorSet::C,
+        ] {
+            let metrics = mirrors.metrics(Coordinates::Lattice).unwrap();
+            let vertices: Vec<_> = mirrors
+                .vertex_orbits()
+                .iter()
+                .flat_map(|(vertex, _)| vertex.orbit.iter().map(|point| widen(point.vec())))
+                .collect();
+            for facet in &metrics.facet_distances {
+                // no vertex is further along the normal than the facet's own
+                let normal = widen(mirrors.face_center(facet.face_type).vertex().vec());
+                let unit = normal.map(|x| x as f64) / (normal.dot(&normal) as f64).sqrt();
+                let furthest = vertices
+                    .iter()
+                    .map(|v| v.map(|x| x as f64).dot(&unit))
+                    .fold(f64::NEG_INFINITY, f64::max);
+                assert!(
+                    (furthest - facet.distance.to_f64()).abs() < 1e-9,
+                    "{mirrors:?}"
+                );
+            }
+        }
+    }
+}
@@ -1,76 +1,191 @@
+use crate::checkpoint::Checkpoint;
+use crate::compress::Compression;
+use crate::coords::Coordinates;
+use crate::coords::VertexFormat;
 use crate::e8::E8;
+use crate::error::Error;
+use crate::error::Result;
+use crate::metric::Vec8i;
+use crate::metric::widen;
 use crate::point::D8;
 use crate::point::Orbit;
+use crate::point::OrbitTable;
+use crate::point::PackedPoint;
 use crate::point::Point;
+use crate::point::Symmetry;
+use crate::progress::CountingWriter;
+use crate::progress::Progress;
+use crate::progress::ProgressMode;
+use crate::shard::Shard;
+use crate::shard::shard_line;
 use fxhash::FxHashMap;
 use fxhash::FxHashSet;
 
-use crate::Mirror;
+use crate::e8::Mirror;
 use crate::e8::MirrorSet;
-use std::collections::VecDeque;
+use crate::e8::polygon;
+use crate::orient::coweight;
+use crate::orient::is_reversed;
+use crate::orient::lattice_frame;
+use crate::orient::primitive;
+use std::cell::OnceCell;
 use std::fmt::Display;
 use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
 
-fn num_length_i16(x: i16) -> u64 {
+fn num_length_u64(x: u64) -> u64 {
     x.to_string().len() as u64
 }
 
-fn num_length_usize(x: usize) -> u64 {
-    x.to_string().len() as u64
+/// Total length of the decimal numbers less than `n`
+fn digits_below(n: u64) -> u64 {
+    let mut total = 0;
+    let mut low = 0;
+    let mut length = 1;
+    while let Some(high) = 10u64.checked_pow(length as u32)
+        && high < n
+    {
+        total += (high - low) * length;
+        low = high;
+        length += 1;
+    }
+    total + (n - low) * length
 }
 
-fn num_length_u64(x: u64) -> u64 {
-    x.to_string().len() as u64
+/// Total length of the decimal numbers in `start..end`
+fn digits_in_range(start: u64, end: u64) -> u64 {
+    digits_below(end) - digits_below(start)
+}
+
+/// Everything before the first vertex line
+fn off_header(point_sets: &[PointSet; 9]) -> String {
+    let mut header = format!(
+        "8OFF\n{} {} {} ",
+        point_sets[0].len(),
+        point_sets[2].len(),
+        point_sets[1].len()
+    );
+    // intentionally omitting 8
+    for point_set in &point_sets[3..8] {
+        header += &format!("{} ", point_set.len());
+    }
+    header += "\n\n# Vertices\n";
+    header
 }
 
+/// Disjoint union of orbits, of D8 unless given, indexing every point by its orbit's offset plus
+/// its orbit index
 #[derive(Debug, Clone)]
-struct PointSet {
-    orbits: Vec<(Orbit, (E8, D8))>,
-    lookup: FxHashMap<Orbit, u64>,
+pub struct PointSet<G = D8> {
+    orbits: Vec<(Orbit<8, G>, (E8, G))>,
+    /// Offset of every orbit, in the order of `orbits`
+    offsets: Vec<u64>,
+    /// Offset and position in `orbits` of every orbit
+    lookup: FxHashMap<Orbit<8, G>, (u64, usize)>,
     len: u64,
 }
 
 // TODO: make the iterator return what E8 you need to get there
-impl PointSet {
-    fn new(iter: impl Iterator<Item = (Point, E8)>) -> Self {
+impl<G: Symmetry<8>> PointSet<G> {
+    /// Point set of the orbits of the given points, each paired with an `E8` taking the original
+    /// point to it
+    pub fn new(iter: impl Iterator<Item = (Point<8, G>, E8)>) -> Self {
         let mut orbits = Vec::new();
+        let mut offsets = Vec::new();
         let mut lookup = FxHashMap::from_iter([]);
         let mut offset = 0;
         for (point, e8) in iter {
             // point = original * e8
             // point = orbitrep * d8
             // orbitrep = point * d8.inv() = original * e8 * d8.inv()
-            orbits.push((point.orbit, (e8, point.d8.inv())));
-            lookup.insert(point.orbit, offset);
+            lookup.insert(point.orbit, (offset, orbits.len()));
+            orbits.push((point.orbit, (e8, point.element.inv())));
+            offsets.push(offset);
             offset += point.orbit.size();
         }
         Self {
             orbits,
+            offsets,
             lookup,
             len: offset,
         }
     }
 
-    fn len(&self) -> u64 {
+    pub fn len(&self) -> u64 {
         self.len
     }
 
-    fn index(&self, point: Point) -> u64 {
-        let offset = self.lookup[&point.orbit];
-        offset + point.orbit_index()
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn index(&self, point: Point<8, G>) -> Result<u64> {
+        let (offset, _) = self
+            .lookup
+            .get(&point.orbit)
+            .ok_or(Error::unknown_orbit(point.orbit))?;
+        Ok(offset + point.orbit_index())
+    }
+
+    /// Point at `index`, the inverse of [`index`](Self::index), or `None` past the last point
+    pub fn get(&self, index: u64) -> Option<Point<8, G>> {
+        if index >= self.len {
+            return None;
+        }
+        let i = self.orbit_at(index);
+        self.orbits[i].0.nth(index - self.offsets[i])
+    }
+
+    /// Position in `orbits` of the orbit containing `index`, or `orbits.len()` past the end
+    fn orbit_at(&self, index: u64) -> usize {
+        if index >= self.len {
+            return self.orbits.len();
+        }
+        self.offsets.partition_point(|&offset| offset <= index) - 1
+    }
+
+    /// `E8` and element of `G` taking the original point to `point`, the same wherever `point`
+    /// came from
+    pub fn transform(&self, point: Point<8, G>) -> Result<(E8, G)> {
+        let &(_, i) = self
+            .lookup
+            .get(&point.orbit)
+            .ok_or(Error::unknown_orbit(point.orbit))?;
+        let (_, (e8, d8)) = self.orbits[i];
+        Ok((e8, d8 * point.element))
+    }
+
+    /// Points in index order, with the `E8` and element of `G` taking the original point to them
+    pub fn iter(&self) -> impl Iterator<Item = (Point<8, G>, (E8, G))> {
+        self.orbits.iter().flat_map(|(orbit, (e8, d8))| {
+            orbit
+                .iter()
+                .map(|point| (point, (*e8, *d8 * point.element)))
+        })
     }
 
-    fn iter(&self) -> impl Iterator<Item = (Point, (E8, D8))> {
-        self.orbits
+    /// Same as `iter().skip(start)`, without going through the skipped orbits
+    pub fn iter_from(&self, start: u64) -> impl Iterator<Item = (Point<8, G>, (E8, G))> {
+        let first = self.orbit_at(start);
+        let start = self.offsets.get(first).map_or(0, |offset| start - offset);
+        self.orbits[first..]
             .iter()
-            .flat_map(|(orbit, (e8, d8))| orbit.iter().map(|point| (point, (*e8, *d8 * point.d8))))
+            .enumerate()
+            .flat_map(move |(i, (orbit, (e8, d8)))| {
+                orbit
+                    .iter()
+                    .skip(if i == 0 { start as usize } else { 0 })
+                    .map(|point| (point, (*e8, *d8 * point.element)))
+            })
     }
 }
 
-pub fn write_spaced<T: Display>(
+pub(crate) fn write_spaced<T: Display>(
     mut writer: impl Write,
     iter: impl Iterator<Item = T>,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<()> {
     let mut is_first = true;
     for item in iter {
         if is_first {
@@ -83,6 +198,170 @@ pub fn write_spaced<T: Display>(
     Ok(())
 }
 
+/// Position of an element line in an .off, ordered the way they are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OffPosition {
+    /// Dimension of the element, 0 for vertices
+    pub dim: usize,
+    /// Index of the element's type in `face_types()[dim]`
+    pub face_type: usize,
+    /// Index of the element among those of its type
+    pub element: u64,
+}
+
+impl OffPosition {
+    pub const START: Self = Self {
+        dim: 0,
+        face_type: 0,
+        element: 0,
+    };
+}
+
+/// Options for [`MirrorSet::write_off`]
+#[derive(Debug, Clone, Default)]
+pub struct OffOptions {
+    /// Highest dimension of faces to write, at most 8, all by default
+    pub dim_limit: Option<usize>,
+    pub progress: ProgressMode,
+    /// Where to periodically save a [`Checkpoint`], removed once the write finishes
+    pub checkpoint: Option<PathBuf>,
+    /// Checkpoint to continue from, with the writer positioned right after its `bytes`
+    pub resume: Option<Checkpoint>,
+    /// Write only this shard, to be put together with the others by [`merge_shards`]
+    ///
+    /// [`merge_shards`]: crate::merge_shards
+    pub shard: Option<Shard>,
+    /// Sign the subfaces of faces of dimension 3 or more to orient every face consistently, a
+    /// subface being listed as `-i` instead of `i` when it is oriented against the face, as
+    /// [`check_boundaries`](crate::check_boundaries) checks
+    pub oriented: bool,
+    /// Coordinate system of the vertices
+    pub coordinates: Coordinates,
+}
+
+const COMPRESSION_SAMPLES: u64 = 16;
+const COMPRESSION_SAMPLE_ELEMENTS: u64 = 2000;
+
+/// Position of the `element`th line among the `runs` of element lines of each face type, or
+/// `None` if it is past the end
+fn element_position(runs: &[(usize, usize, u64)], mut element: u64) -> Option<OffPosition> {
+    for &(dim, face_type, count) in runs {
+        if element < count {
+            return Some(OffPosition {
+                dim,
+                face_type,
+                element,
+            });
+        }
+        element -= count;
+    }
+    None
+}
+
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+const CHECKPOINT_CHECK_ELEMENTS: u64 = 1024;
+
+/// Writer of the element lines of one .off from `start` up to `end`, or to the end of the .off if
+/// `end` is `None`
+struct OffWriter<'a, W: Write> {
+    writer: CountingWriter<W>,
+    progress: Progress,
+    options: &'a OffOptions,
+    mirrors: MirrorSet,
+    dim_limit: usize,
+    start: OffPosition,
+    end: Option<OffPosition>,
+    last_checkpoint: Instant,
+}
+
+impl<W: Write> OffWriter<'_, W> {
+    fn in_range(&self, position: OffPosition) -> bool {
+        position >= self.start && self.end.is_none_or(|end| position < end)
+    }
+
+    /// Whether any elements of `(dim, face_type)` are to be written
+    fn wanted(&self, dim: usize, face_type: usize) -> bool {
+        let first = OffPosition {
+            dim,
+            face_type,
+            element: 0,
+        };
+        (dim, face_type) >= (self.start.dim, self.start.face_type)
+            && self.end.is_none_or(|end| first < end)
+    }
+
+    /// Write `text` if it comes at the start of `dim` and that is not already written
+    fn section_start(&mut self, dim: usize, text: &str) -> Result<()> {
+        let position = OffPosition {
+            dim,
+            face_type: 0,
+            element: 0,
+        };
+        if self.in_range(position) {
+            write!(self.writer, "{text}")?;
+        }
+        Ok(())
+    }
+
+    /// Write one line per point of `point_set` using `line`
+    fn elements(
+        &mut self,
+        dim: usize,
+        face_type: usize,
+        label: String,
+        point_set: &PointSet,
+        mut line: impl FnMut(&mut CountingWriter<W>, Point, E8, D8) -> Result<()>,
+    ) -> Result<()> {
+        if !self.wanted(dim, face_type) {
+            return Ok(());
+        }
+        let start = if (dim, face_type) == (self.start.dim, self.start.face_type) {
+            self.start.element
+        } else {
+            0
+        };
+        let end = match self.end {
+            Some(end) if (dim, face_type) == (end.dim, end.face_type) => end.element,
+            _ => point_set.len(),
+        };
+        self.progress
+            .section(label, point_set.len(), self.writer.count());
+        self.progress.elements(start, self.writer.count());
+        for (element, (point, (e8, d8))) in (start + 1..=end).zip(point_set.iter_from(start)) {
+            line(&mut self.writer, point, e8, d8)?;
+            self.progress.elements(1, self.writer.count());
+            if element % CHECKPOINT_CHECK_ELEMENTS == 0
+                && self.last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL
+            {
+                self.checkpoint(OffPosition {
+                    dim,
+                    face_type,
+                    element,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn checkpoint(&mut self, position: OffPosition) -> Result<()> {
+        if let Some(path) = &self.options.checkpoint {
+            self.writer.flush()?;
+            Checkpoint {
+                mirrors: self.mirrors,
+                dim_limit: self.dim_limit,
+                shard: self.options.shard,
+                oriented: self.options.oriented,
+                coordinates: self.options.coordinates,
+                position,
+                bytes: self.writer.count(),
+            }
+            .save(path)?;
+        }
+        self.last_checkpoint = Instant::now();
+        Ok(())
+    }
+}
+
 impl MirrorSet {
     pub fn face_types(self) -> [Vec<Self>; 9] {
         let mut face_types = [const { Vec::new() }; 9];
@@ -112,175 +391,676 @@ impl MirrorSet {
             .fold(Self::empty(), |x, y| x | Self::from_mirror(y))
     }
 
-    pub fn off_size_estimate(self) -> u64 {
-        let mut size = 0;
-        let face_types = self.face_types();
-        let point_sets: [PointSet; 9] = face_types.clone().map(|types| {
+    /// Vertices of the 2-face of type `face_type` around [`vertex`](Self::vertex), in cyclic
+    /// order
+    pub fn polygon(self, face_type: Self) -> Result<Vec<Point>> {
+        let &[m1, m2] = &face_type.mirrors().collect::<Vec<_>>()[..] else {
+            return Err(Error::Invariant(format!(
+                "2-face {face_type:?} does not have two mirrors"
+            )));
+        };
+        Ok(polygon(self.vertex(), [m1.mat(), m2.mat()], m1.link(m2)))
+    }
+
+    fn point_sets(self, face_types: &[Vec<Self>; 9]) -> [PointSet; 9] {
+        face_types.clone().map(|types| {
             PointSet::new(
                 types
                     .iter()
                     .flat_map(|face| self.face_center(*face).vertex_orbits()),
             )
-        });
-        // dbg!(face_types, face_centers);
+        })
+    }
+
+    /// Images of `point` under the group generated by `mirrors` in breadth-first order
+    pub(crate) fn orbit_under(point: Point, mirrors: Self) -> PackedOrbit {
+        PackedOrbit::search(point, mirrors, false)
+    }
 
+    /// Images of `point` under the group generated by `mirrors` in breadth-first order, keeping
+    /// what [`PackedOrbit::iter_words`] needs to give an element of the group taking `point` to
+    /// each
+    pub(crate) fn orbit_words(point: Point, mirrors: Self) -> PackedOrbit {
+        PackedOrbit::search(point, mirrors, true)
+    }
+
+    /// Basis of the span of the face of type `face_type` around the vertex, with the
+    /// orientation [`orient`](crate::orient) gives it
+    fn orientation_basis(self, face_type: Self, dim: usize) -> Result<Vec<Point>> {
+        if dim == 2 {
+            let vertices = self.polygon(face_type)?;
+            let [x0, x1, x2] = [0, 1, 2].map(|i| vertices[i].vec());
+            Ok(vec![Point::new(x1 - x0), Point::new(x2 - x0)])
+        } else {
+            Ok(face_type.mirrors().map(|m| Point::new(m.pole())).collect())
+        }
+    }
+
+    /// Centers of the `dim - 1`-faces of the face of type `face_type` around the vertex, each
+    /// with its outward normal and the position of its type in `face_types[dim - 1]`
+    fn oriented_subfaces(
+        self,
+        face_types: &[Vec<Self>; 9],
+        face_type: Self,
+        dim: usize,
+    ) -> Vec<(Point, Vec8i, usize)> {
+        let poles: Vec<_> = face_type.mirrors().map(|m| widen(m.pole())).collect();
+        let vertex = widen(self.vertex().vec());
+        let mut subfaces = Vec::new();
+        for (s, &subface_type) in face_types[dim - 1].iter().enumerate() {
+            let Some(m) = face_type
+                .mirrors()
+                .position(|m| !subface_type.has_mirror(m))
+                .filter(|_| face_type.contains(subface_type))
+            else {
+                continue;
+            };
+            // the subface around the vertex is the face's other mirror's side of the face
+            let coweight = coweight(&poles, m);
+            let normal = coweight * vertex.dot(&coweight).signum();
+            for (center, word) in
+                Self::orbit_words(self.face_center(subface_type).vertex(), face_type).iter_words()
+            {
+                let normal = primitive(normal * lattice_frame(word, D8::identity()));
+                subfaces.push((center, normal, s));
+            }
+        }
+        subfaces
+    }
+
+    /// Centers of the `dim - 1`-faces of the face of type `face_type` around the vertex
+    fn subfaces(self, face_types: &[Vec<Self>; 9], face_type: Self, dim: usize) -> Vec<Point> {
+        // faces of different types have centers in different orbits
+        let mut subfaces = Vec::new();
+        for &subface_type in &face_types[dim - 1] {
+            if face_type.contains(subface_type) {
+                subfaces.extend(
+                    Self::orbit_under(self.face_center(subface_type).vertex(), face_type).iter(),
+                );
+            }
+        }
+        subfaces
+    }
+
+    /// Number of faces of each dimension below 8
+    pub fn f_vector(self) -> [u64; 8] {
+        let face_types = self.face_types();
+        std::array::from_fn(|dim| {
+            face_types[dim]
+                .iter()
+                .map(|&face_type| self.face_center(face_type).vertex_count())
+                .sum()
+        })
+    }
+
+    /// Exact size in bytes of the .off written by [`write_off`](Self::write_off) with
+    /// `dim_limit`, without shards or compression
+    pub fn off_size(self, dim_limit: Option<usize>) -> Result<u64> {
+        let options = OffOptions {
+            dim_limit,
+            ..Default::default()
+        };
+        Ok(self.off_layout(&options)?.bytes())
+    }
+
+    fn off_bytes(
+        self,
+        face_types: &[Vec<Self>; 9],
+        point_sets: &[PointSet; 9],
+        dim_limit: usize,
+        format: &VertexFormat,
+    ) -> u64 {
+        let mut size = off_header(point_sets).len() as u64;
         for (orbit, _) in &point_sets[0].orbits {
-            size += (8 * num_length_i16(orbit.rep.max()) + 12) * orbit.size();
+            size += format.orbit_length(*orbit);
+        }
+
+        if dim_limit >= 1 {
+            size += "\n# Faces\n".len() as u64;
+            for &face_type in &face_types[2] {
+                let vertices = self.subface_count(face_type, Self::empty());
+                size += self.faces_size(face_type, &[(0, point_sets[0].len(), vertices)]);
+            }
         }
 
-        for i in 2..8 {
-            for &face_type in face_types[i].iter() {
-                // kind of overkill but if it's a problem wait until you see what comes next
-                let mut subfaces = FxHashSet::from_iter([]);
+        for i in 3..=dim_limit {
+            size += format!("\n# {i}-faces\n").len() as u64;
+            for &face_type in &face_types[i] {
+                // subfaces of each type take up a contiguous range of indices
+                let mut offset = 0;
+                let mut subface_ranges = Vec::new();
                 for &subface_type in &face_types[i - 1] {
+                    let count = self.face_center(subface_type).vertex_count();
                     if face_type.contains(subface_type) {
-                        let mut stack =
-                            VecDeque::from_iter([self.face_center(subface_type).vertex()]);
-                        while let Some(v) = stack.pop_front() {
-                            if subfaces.insert(v) {
-                                for mirror in face_type.mirrors() {
-                                    stack.push_back(v * mirror.mat());
-                                }
-                            }
-                        }
+                        let per_face = self.subface_count(face_type, subface_type);
+                        subface_ranges.push((offset, offset + count, per_face));
                     }
+                    offset += count;
                 }
-
-                size += (num_length_usize(subfaces.len())
-                    + subfaces.len() as u64 * (1 + num_length_u64(point_sets[i - 1].len())))
-                    * self.face_center(face_type).vertex_count();
+                size += self.faces_size(face_type, &subface_ranges);
             }
         }
 
-        size
+        // the final blank line
+        size + 1
+    }
+
+    /// Size of the lines of all faces of type `face_type`, whose subfaces are indexed by the
+    /// ranges in `subface_ranges`, each with the number of its subfaces that every face has
+    fn faces_size(self, face_type: Self, subface_ranges: &[(u64, u64, u64)]) -> u64 {
+        let faces = self.face_center(face_type).vertex_count();
+        let per_face: u64 = subface_ranges.iter().map(|range| range.2).sum();
+        // every subface is in the same number of faces, so each index in a range appears
+        // faces * per_face / len times
+        let indices: u64 = subface_ranges
+            .iter()
+            .map(|&(start, end, per_face)| {
+                digits_in_range(start, end) * (faces * per_face / (end - start))
+            })
+            .sum();
+        // count, a space before every index, and a newline
+        faces * (num_length_u64(per_face) + per_face + 1) + indices
     }
 
-    pub fn write_off(
+    /// Number of element lines of each face type, in the order they are written
+    fn element_runs(
         self,
-        mut writer: impl Write,
-        dim_limit: Option<usize>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let dim_limit = dim_limit.unwrap_or(8);
+        face_types: &[Vec<Self>; 9],
+        dim_limit: usize,
+    ) -> Vec<(usize, usize, u64)> {
+        let mut runs = vec![(0, 0, self.vertex_count())];
+        let dims = (dim_limit >= 1)
+            .then_some(2)
+            .into_iter()
+            .chain(3..=dim_limit);
+        for dim in dims {
+            for (t, &face_type) in face_types[dim].iter().enumerate() {
+                runs.push((dim, t, self.face_center(face_type).vertex_count()));
+            }
+        }
+        runs
+    }
+
+    /// Positions where `shard` starts and ends, and its share of the elements, with both ends
+    /// at the start of an orbit of `point_sets`, so no orbit or face type is split across shards
+    fn shard_range(
+        runs: &[(usize, usize, u64)],
+        point_sets: &[PointSet; 9],
+        shard: Shard,
+    ) -> Result<(OffPosition, Option<OffPosition>, f64)> {
+        let total = runs.iter().map(|run| run.2).sum();
+        // the first element of every orbit, each dimension's after those of the ones before
+        let mut dims: Vec<_> = runs.iter().map(|run| run.0).collect();
+        dims.dedup();
+        let mut orbit_starts = Vec::new();
+        let mut base = 0;
+        for dim in dims {
+            orbit_starts.extend(point_sets[dim].offsets.iter().map(|offset| base + offset));
+            base += point_sets[dim].len();
+        }
+        debug_assert_eq!(base, total);
+        // the last orbit to start by an even split, which is the same for the shards on both
+        // sides of it
+        let snap = |element| orbit_starts[orbit_starts.partition_point(|&s| s <= element) - 1];
+        let (start, end) = shard.elements(total);
+        let (start, end) = (snap(start), if shard.is_last() { total } else { snap(end) });
+        let position = |element| {
+            element_position(runs, element).ok_or_else(|| {
+                Error::Invariant(format!("shard {shard} starts past the {total} elements"))
+            })
+        };
+        Ok((
+            position(start)?,
+            if shard.is_last() {
+                None
+            } else {
+                Some(position(end)?)
+            },
+            (end - start) as f64 / total as f64,
+        ))
+    }
+
+    /// Estimated size of the .off once compressed, from compressing evenly spaced samples of it
+    pub fn compressed_size_estimate(self, compression: Compression) -> Result<u64> {
+        let options = OffOptions::default();
+        let layout = self.off_layout(&options)?;
+        Ok((layout.bytes() as f64 * layout.compression_ratio(compression)?) as u64)
+    }
+
+    /// Size of the file [`write_off`](Self::write_off) writes with `options` through
+    /// `compression`, exact for a whole uncompressed unoriented .off, an upper bound if it is
+    /// oriented, and estimated otherwise
+    pub fn output_size(self, options: &OffOptions, compression: Compression) -> Result<u64> {
+        let layout = self.off_layout(options)?;
+        layout.output_size(layout.compression_ratio(compression)?)
+    }
+
+    pub fn write_off(self, writer: impl Write, options: &OffOptions) -> Result<()> {
+        self.off_layout(options)?.write_off(writer)
+    }
+
+    /// Face types and point sets of the .off written with `options`, to size and write it
+    /// without computing them again
+    pub fn off_layout(self, options: &OffOptions) -> Result<OffLayout<'_>> {
+        let dim_limit = options.dim_limit.unwrap_or(8);
+        if dim_limit > 8 {
+            return Err(Error::Options(format!(
+                "dimension limit {dim_limit} is past the polytope's 8"
+            )));
+        }
+        if let Some(shard) = options.shard
+            && shard.index >= shard.count
+        {
+            return Err(Error::Shard(format!(
+                "bad shard {shard}, expected i/n with 0 <= i < n"
+            )));
+        }
         let face_types = self.face_types();
-        let point_sets: [PointSet; 9] = face_types.clone().map(|types| {
-            PointSet::new(
-                types
-                    .iter()
-                    .flat_map(|face| self.face_center(*face).vertex_orbits()),
+        let point_sets = self.point_sets(&face_types);
+        Ok(OffLayout {
+            mirrors: self,
+            options,
+            dim_limit,
+            format: VertexFormat::new(options.coordinates, self)?,
+            face_types,
+            point_sets,
+            bytes: OnceCell::new(),
+        })
+    }
+}
+
+/// Face types and point sets of the .off of a polytope written with some [`OffOptions`],
+/// computed once for everything that sizes and writes it
+pub struct OffLayout<'a> {
+    mirrors: MirrorSet,
+    options: &'a OffOptions,
+    dim_limit: usize,
+    format: VertexFormat,
+    face_types: [Vec<MirrorSet>; 9],
+    point_sets: [PointSet; 9],
+    /// Exact size of the whole .off without shards, orientation or compression
+    bytes: OnceCell<u64>,
+}
+
+impl OffLayout<'_> {
+    /// Exact size of the whole .off without shards, orientation or compression
+    pub fn bytes(&self) -> u64 {
+        *self.bytes.get_or_init(|| {
+            self.mirrors.off_bytes(
+                &self.face_types,
+                &self.point_sets,
+                self.dim_limit,
+                &self.format,
             )
-        });
-        // dbg!(face_types, face_centers);
-
-        write!(writer, "8OFF\n")?;
-        write!(writer, "{} ", point_sets[0].len())?;
-        write!(writer, "{} ", point_sets[2].len())?;
-        write!(writer, "{} ", point_sets[1].len())?;
-        for i in 3..8 {
-            // intentionally omitting 8
-            write!(writer, "{} ", point_sets[i].len())?;
-        }
-        write!(writer, "\n\n")?;
-
-        if dim_limit >= 0 {
-            println!("Vertices");
-            write!(writer, "# Vertices\n")?;
-            for (vertex, _) in point_sets[0].iter() {
-                write_spaced(&mut writer, vertex.vec().iter())?;
-                write!(writer, "\n")?;
-            }
-            write!(writer, "\n")?;
+        })
+    }
+
+    /// Ratio of compressed to uncompressed size of evenly spaced samples of the whole .off with
+    /// this layout's dimension limit, orientation and coordinates, 1 without compression
+    pub fn compression_ratio(&self, compression: Compression) -> Result<f64> {
+        if compression == Compression::None {
+            return Ok(1.0);
         }
+        let mirrors = self.mirrors;
+        let runs = mirrors.element_runs(&self.face_types, self.dim_limit);
+        let total: u64 = runs.iter().map(|run| run.2).sum();
+        let options = OffOptions {
+            dim_limit: Some(self.dim_limit),
+            progress: ProgressMode::Quiet,
+            oriented: self.options.oriented,
+            coordinates: self.options.coordinates,
+            ..Default::default()
+        };
 
-        if dim_limit >= 1 {
-            write!(writer, "# Faces\n")?;
-            for &face_type in face_types[2].iter() {
-                println!("Faces: {face_type:?}");
-                let vertex = self.vertex();
-                let &[m1, m2] = &face_type.mirrors().collect::<Vec<_>>()[..] else {
-                    panic!("not two")
-                };
+        let mut sample = Vec::new();
+        for i in 0..COMPRESSION_SAMPLES {
+            let start = total * i / COMPRESSION_SAMPLES;
+            let end =
+                (start + COMPRESSION_SAMPLE_ELEMENTS).min(total * (i + 1) / COMPRESSION_SAMPLES);
+            let (Some(start), end) = (element_position(&runs, start), element_position(&runs, end))
+            else {
+                continue;
+            };
+            let mut off = OffWriter {
+                writer: CountingWriter::new(&mut sample),
+                progress: Progress::new(ProgressMode::Quiet, 0),
+                options: &options,
+                mirrors,
+                dim_limit: self.dim_limit,
+                start,
+                end,
+                last_checkpoint: Instant::now(),
+            };
+            mirrors.write_range(&mut off, &self.face_types, &self.point_sets)?;
+        }
 
-                let vertices = if m1.link(m2) == 2 {
-                    vec![
-                        vertex,
-                        vertex * m1.mat(),
-                        vertex * m2.mat() * m1.mat(),
-                        vertex * m2.mat(),
-                    ]
-                } else if self & face_type == face_type {
-                    vec![
-                        vertex,
-                        vertex * m1.mat(),
-                        vertex * m2.mat() * m1.mat(),
-                        vertex * m1.mat() * m2.mat() * m1.mat(),
-                        vertex * m1.mat() * m2.mat(),
-                        vertex * m2.mat(),
-                    ]
-                } else {
-                    vec![
-                        vertex,
-                        vertex * m2.mat() * m1.mat(),
-                        vertex * m1.mat() * m2.mat(),
-                    ]
-                };
+        Ok(compression.compressed_len(&sample)? as f64 / sample.len().max(1) as f64)
+    }
 
-                // already did this once, is that ok
-                for (_point, (e8, d8)) in
-                    PointSet::new(self.face_center(face_type).vertex_orbits().into_iter()).iter()
-                {
-                    write!(writer, "{}", vertices.len())?;
+    /// Size of the file [`write_off`](Self::write_off) writes through a compression with
+    /// `compression_ratio`, as in [`MirrorSet::output_size`]
+    pub fn output_size(&self, compression_ratio: f64) -> Result<u64> {
+        let (mirrors, options, dim_limit) = (self.mirrors, self.options, self.dim_limit);
+        let face_types = &self.face_types;
+        let mut size = self.bytes();
+        if options.oriented {
+            // at most a minus sign before every subface of a face of dimension 3 or more
+            size += (3..=dim_limit)
+                .flat_map(|i| face_types[i].iter().map(move |&face_type| (i, face_type)))
+                .map(|(i, face_type)| {
+                    let subfaces: u64 = face_types[i - 1]
+                        .iter()
+                        .filter(|&&subface_type| face_type.contains(subface_type))
+                        .map(|&subface_type| mirrors.subface_count(face_type, subface_type))
+                        .sum();
+                    mirrors.face_center(face_type).vertex_count() * subfaces
+                })
+                .sum::<u64>();
+        }
+        if let Some(shard) = options.shard {
+            let runs = mirrors.element_runs(face_types, dim_limit);
+            let (_, _, share) = MirrorSet::shard_range(&runs, &self.point_sets, shard)?;
+            size = (size as f64 * share) as u64
+                + shard_line(
+                    shard,
+                    mirrors,
+                    dim_limit,
+                    options.oriented,
+                    options.coordinates,
+                )
+                .len() as u64;
+        }
+        Ok((size as f64 * compression_ratio) as u64)
+    }
 
-                    for &vertex in &vertices {
-                        write!(writer, " {}", point_sets[0].index(vertex * e8 * d8))?;
-                    }
-                    write!(writer, "\n")?;
+    pub fn write_off(&self, writer: impl Write) -> Result<()> {
+        let (mirrors, options, dim_limit) = (self.mirrors, self.options, self.dim_limit);
+        if let Some(checkpoint) = &options.resume {
+            checkpoint.check(
+                mirrors,
+                dim_limit,
+                options.shard,
+                options.oriented,
+                options.coordinates,
+            )?;
+        }
+        let (start, end, share) = match options.shard {
+            Some(shard) => MirrorSet::shard_range(
+                &mirrors.element_runs(&self.face_types, dim_limit),
+                &self.point_sets,
+                shard,
+            )?,
+            None => (OffPosition::START, None, 1.0),
+        };
+        let start_bytes = options.resume.map_or(0, |checkpoint| checkpoint.bytes);
+        let bytes_estimate = match options.progress {
+            ProgressMode::Quiet => 0,
+            _ => (self.bytes() as f64 * share) as u64,
+        };
+        let mut progress = Progress::new(options.progress, bytes_estimate);
+        progress.resume_at(start_bytes);
+        let mut off = OffWriter {
+            writer: CountingWriter::starting_at(writer, start_bytes),
+            progress,
+            options,
+            mirrors,
+            dim_limit,
+            start: options
+                .resume
+                .map_or(start, |checkpoint| checkpoint.position.max(start)),
+            end,
+            last_checkpoint: Instant::now(),
+        };
+
+        if let (Some(shard), None) = (options.shard, options.resume) {
+            write!(
+                off.writer,
+                "{}",
+                shard_line(
+                    shard,
+                    mirrors,
+                    dim_limit,
+                    options.oriented,
+                    options.coordinates
+                )
+            )?;
+        }
+
+        mirrors.write_range(&mut off, &self.face_types, &self.point_sets)?;
+
+        off.writer.flush()?;
+        off.progress.finish(off.writer.count());
+        if let Some(path) = &options.checkpoint
+            && path.exists()
+        {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl MirrorSet {
+    /// Write everything from `off.start` up to `off.end`
+    fn write_range<W: Write>(
+        self,
+        off: &mut OffWriter<'_, W>,
+        face_types: &[Vec<Self>; 9],
+        point_sets: &[PointSet; 9],
+    ) -> Result<()> {
+        let dim_limit = off.dim_limit;
+        let format = VertexFormat::new(off.options.coordinates, self)?;
+        off.section_start(0, &off_header(point_sets))?;
+
+        off.elements(
+            0,
+            0,
+            "Vertices".to_owned(),
+            &point_sets[0],
+            |writer, vertex, _, _| format.write_vertex(writer, vertex.vec()),
+        )?;
+
+        let oriented = off.options.oriented;
+        if dim_limit >= 1 {
+            off.section_start(2, "\n# Faces\n")?;
+            for (t, &face_type) in face_types[2].iter().enumerate() {
+                if !off.wanted(2, t) {
+                    continue;
                 }
+                let vertices = self.polygon(face_type)?;
+
+                // already did this once, is that ok
+                off.elements(
+                    2,
+                    t,
+                    format!("Faces: {face_type:?}"),
+                    &PointSet::new(self.face_center(face_type).vertex_orbits().into_iter()),
+                    |writer, _, e8, d8| {
+                        // the E8 and D8 are the ones every face line finds for the polygon, so
+                        // oriented polygons go the same way in all of them
+                        write!(writer, "{}", vertices.len())?;
+                        for &vertex in &vertices {
+                            write!(writer, " {}", point_sets[0].index(vertex * e8 * d8)?)?;
+                        }
+                        writeln!(writer)?;
+                        Ok(())
+                    },
+                )?;
             }
-            write!(writer, "\n")?;
         }
 
         for i in 3..=dim_limit {
-            write!(writer, "# {i}-faces\n")?;
-            for &face_type in face_types[i].iter() {
-                println!("{i}-faces: {face_type:?}");
-                let mut subfaces = FxHashSet::from_iter([]);
-                for &subface_type in &face_types[i - 1] {
-                    if face_type.contains(subface_type) {
-                        let mut stack =
-                            VecDeque::from_iter([self.face_center(subface_type).vertex()]);
-                        while let Some(v) = stack.pop_front() {
-                            if subfaces.insert(v) {
-                                for mirror in face_type.mirrors() {
-                                    stack.push_back(v * mirror.mat());
-                                }
-                            }
-                        }
-                    }
+            off.section_start(i, &format!("\n# {i}-faces\n"))?;
+            for (t, &face_type) in face_types[i].iter().enumerate() {
+                if !off.wanted(i, t) {
+                    continue;
+                }
+                let face_set =
+                    PointSet::new(self.face_center(face_type).vertex_orbits().into_iter());
+                if oriented {
+                    self.write_oriented_faces(off, face_types, point_sets, i, t, &face_set)?;
+                    continue;
                 }
+                let subfaces = self.subfaces(face_types, face_type, i);
+                let mut indices = Vec::with_capacity(subfaces.len());
 
                 // already did this once, is that ok
-                for (_point, (e8, d8)) in
-                    PointSet::new(self.face_center(face_type).vertex_orbits().into_iter()).iter()
-                {
-                    write!(writer, "{}", subfaces.len())?;
-                    for &subface in &subfaces {
-                        write!(writer, " {}", point_sets[i - 1].index(subface * e8 * d8))?;
+                off.elements(
+                    i,
+                    t,
+                    format!("{i}-faces: {face_type:?}"),
+                    &face_set,
+                    |writer, _, e8, d8| {
+                        // sorted, so that lines do not depend on the order of the hash set
+                        indices.clear();
+                        for &subface in &subfaces {
+                            indices.push(point_sets[i - 1].index(subface * e8 * d8)?);
+                        }
+                        indices.sort_unstable();
+                        write!(writer, "{}", indices.len())?;
+                        for index in &indices {
+                            write!(writer, " {index}")?;
+                        }
+                        writeln!(writer)?;
+                        Ok(())
+                    },
+                )?;
+            }
+        }
+        if off.end.is_none() {
+            writeln!(off.writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the `i`-faces of type `face_types[i][t]`, whose point set is `face_set`, with signed
+    /// subfaces
+    fn write_oriented_faces<W: Write>(
+        self,
+        off: &mut OffWriter<'_, W>,
+        face_types: &[Vec<Self>; 9],
+        point_sets: &[PointSet; 9],
+        i: usize,
+        t: usize,
+        face_set: &PointSet,
+    ) -> Result<()> {
+        let face_type = face_types[i][t];
+        let basis = self.orientation_basis(face_type, i)?;
+        let subface_bases = face_types[i - 1]
+            .iter()
+            .map(|&subface_type| self.orientation_basis(subface_type, i - 1))
+            .collect::<Result<Vec<_>>>()?;
+        let subfaces = self.oriented_subfaces(face_types, face_type, i);
+        let mut indices = Vec::with_capacity(subfaces.len());
+        let image = |basis: &[Point], (e8, d8): (E8, D8)| -> Vec<Vec8i> {
+            basis.iter().map(|&v| widen((v * e8 * d8).vec())).collect()
+        };
+
+        off.elements(
+            i,
+            t,
+            format!("{i}-faces: {face_type:?}"),
+            face_set,
+            |writer, point, e8, d8| {
+                let face = image(&basis, point_sets[i].transform(point)?);
+                let placement = lattice_frame(e8, d8);
+                write!(writer, "{}", subfaces.len())?;
+                indices.clear();
+                for &(center, normal, s) in &subfaces {
+                    let subface = center * e8 * d8;
+                    let subface_basis =
+                        image(&subface_bases[s], point_sets[i - 1].transform(subface)?);
+                    let reversed = is_reversed(normal * placement, &subface_basis, &face);
+                    indices.push((point_sets[i - 1].index(subface)?, reversed));
+                }
+                indices.sort_unstable();
+                for (index, reversed) in &indices {
+                    let sign = if *reversed { "-" } else { "" };
+                    write!(writer, " {sign}{index}")?;
+                }
+                writeln!(writer)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Images of a point under a group generated by mirrors in breadth-first order, packed
+pub(crate) struct PackedOrbit {
+    table: OrbitTable,
+    points: Vec<PackedPoint>,
+    /// For each point but the first, the index of the point it was first reached from and the
+    /// mirror taking it there, if words were asked for
+    parents: Vec<(u32, Mirror)>,
+}
+
+impl PackedOrbit {
+    fn search(point: Point, mirrors: MirrorSet, words: bool) -> Self {
+        let mut table = OrbitTable::new();
+        let first = table.pack(point);
+        let mut seen = FxHashSet::from_iter([first]);
+        let mut points = vec![first];
+        let mut parents = Vec::new();
+        // the points found so far are the queue, from `next` on
+        let mut next = 0;
+        while next < points.len() {
+            let v = table.unpack(points[next]);
+            for mirror in mirrors.mirrors() {
+                let image = table.pack(v * mirror.mat());
+                if seen.insert(image) {
+                    points.push(image);
+                    if words {
+                        parents.push((next as u32, mirror));
                     }
-                    write!(writer, "\n")?;
                 }
             }
-            write!(writer, "\n")?;
+            next += 1;
+        }
+        Self {
+            table,
+            points,
+            parents,
         }
+    }
 
-        Ok(())
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        self.points.iter().map(|&packed| self.table.unpack(packed))
+    }
+
+    /// Each point with an element of the group taking the first point to it, for an orbit from
+    /// [`MirrorSet::orbit_words`]
+    pub(crate) fn iter_words(&self) -> impl Iterator<Item = (Point, E8)> + '_ {
+        assert_eq!(
+            self.parents.len() + 1,
+            self.points.len(),
+            "orbit searched without words"
+        );
+        (0..self.points.len()).map(|i| {
+            let mut word = E8::identity();
+            let mut j = i;
+            while j > 0 {
+                let (parent, mirror) = self.parents[j - 1];
+                word = mirror.mat() * word;
+                j = parent as usize;
+            }
+            (self.table.unpack(self.points[i]), word)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::point::B8;
+    use crate::point::Coord;
+    use crate::point::S8;
 
-    fn point_set_test(point: [i16; 8]) {
+    fn point_set_test(point: [Coord; 8]) {
         let point = Point::new(point.into());
         let point_set = PointSet::new([(point, E8::identity())].into_iter());
 
@@ -288,7 +1068,7 @@ mod tests {
         for (i, (point2, (e8, d8))) in point_set.iter().enumerate() {
             assert!(d8.signs_even());
             assert_eq!(point * e8 * d8, point2);
-            assert_eq!(point_set.index(point2), i as u64);
+            assert_eq!(point_set.index(point2).unwrap(), i as u64);
         }
     }
 
@@ -306,4 +1086,338 @@ mod tests {
     fn point_set_e8_d8_00000022() {
         point_set_test([0, 0, 0, 0, 0, 0, 2, 2]);
     }
+
+    #[test]
+    fn point_set_unknown_orbit() {
+        let point_set = PointSet::new([(Point::new([1; 8].into()), E8::identity())].into_iter());
+        let point = Point::new([0, 0, 0, 0, 0, 0, 2, 2].into());
+        assert!(matches!(
+            point_set.index(point),
+            Err(Error::UnknownOrbit { rep, sign }) if (rep, sign) == (point.orbit.rep, point.orbit.sign)
+        ));
+    }
+
+    #[test]
+    fn point_set_iter_from() {
+        let point = Point::new([1, 2, 2, 2, 3, 3, 4, 4].into());
+        let point_set = PointSet::new(
+            [
+                (point, E8::identity()),
+                (Point::new([1; 8].into()), E8::identity()),
+            ]
+            .into_iter(),
+        );
+        let all: Vec<_> = point_set.iter().collect();
+        for start in [0, 1, 1000, point.orbit_size() - 1, point.orbit_size() + 3] {
+            assert!(
+                point_set
+                    .iter_from(start)
+                    .eq(all[start as usize..].iter().copied())
+            );
+        }
+        assert_eq!(point_set.iter_from(point_set.len()).count(), 0);
+    }
+
+    #[test]
+    fn point_set_get() {
+        let mirrors = MirrorSet::A0 | MirrorSet::C;
+        let point_set = PointSet::new(mirrors.vertex_orbits().into_iter());
+        for (i, (point, _)) in point_set.iter().enumerate().step_by(101) {
+            assert_eq!(point_set.get(i as u64), Some(point));
+            assert_eq!(point_set.index(point).unwrap(), i as u64);
+        }
+        let last = point_set.len() - 1;
+        assert_eq!(point_set.index(point_set.get(last).unwrap()).unwrap(), last);
+        assert_eq!(point_set.get(last + 1), None);
+    }
+
+    #[test]
+    fn point_set_other_groups() {
+        // the 8-cube's vertices under B8 and the hypersimplex's under S8
+        let cube = Point::<8, B8>::from_vec([1; 8].into());
+        let simplex = Point::<8, S8>::from_vec([0, 0, 0, 0, 1, 1, 1, 1].into());
+        let cube_set = PointSet::new([(cube, E8::identity())].into_iter());
+        let simplex_set = PointSet::new([(simplex, E8::identity())].into_iter());
+        assert_eq!((cube_set.len(), simplex_set.len()), (256, 70));
+        for i in 0..256 {
+            assert_eq!(cube_set.index(cube_set.get(i).unwrap()).unwrap(), i);
+        }
+        for (i, (point, (_, s8))) in simplex_set.iter().enumerate() {
+            assert_eq!(simplex_set.index(point).unwrap(), i as u64);
+            assert_eq!(simplex.vec() * s8, point.vec());
+        }
+    }
+
+    #[test]
+    fn packed_orbits() {
+        // the vertices of the 7-simplex and 7-orthoplex facets of 4_21
+        let vertex = MirrorSet::A0.vertex();
+        for (face_type, size) in [
+            (MirrorSet::all() - MirrorSet::C, 8),
+            (MirrorSet::all() - MirrorSet::B0, 14),
+        ] {
+            let orbit = MirrorSet::orbit_under(vertex, face_type);
+            let words = MirrorSet::orbit_words(vertex, face_type);
+            assert_eq!((orbit.len(), words.len()), (size, size));
+            assert!(orbit.iter().eq(words.iter()));
+            for (point, word) in words.iter_words() {
+                assert_eq!(vertex * word, point);
+            }
+        }
+    }
+
+    #[test]
+    fn write_off_resume() {
+        let mirrors = MirrorSet::A0;
+        let options = OffOptions {
+            dim_limit: Some(2),
+            progress: ProgressMode::Quiet,
+            ..Default::default()
+        };
+        let mut full = Vec::new();
+        mirrors.write_off(&mut full, &options).unwrap();
+
+        let position = OffPosition {
+            dim: 2,
+            face_type: 0,
+            element: 1000,
+        };
+        let faces = full.windows(8).position(|w| w == b"# Faces\n").unwrap() + 8;
+        let bytes = faces
+            + full[faces..]
+                .split_inclusive(|&b| b == b'\n')
+                .take(1000)
+                .map(|line| line.len())
+                .sum::<usize>();
+
+        let mut resumed = full[..bytes].to_vec();
+        let resume_options = OffOptions {
+            resume: Some(Checkpoint {
+                mirrors,
+                dim_limit: 2,
+                shard: None,
+                oriented: false,
+                coordinates: Coordinates::Lattice,
+                position,
+                bytes: bytes as u64,
+            }),
+            ..options
+        };
+        mirrors.write_off(&mut resumed, &resume_options).unwrap();
+        assert!(resumed == full);
+    }
+
+    #[test]
+    fn write_off_shards() {
+        let mirrors = MirrorSet::A0;
+        let options = OffOptions {
+            dim_limit: Some(2),
+            progress: ProgressMode::Quiet,
+            coordinates: Coordinates::Even,
+            ..Default::default()
+        };
+        let mut full = Vec::new();
+        mirrors.write_off(&mut full, &options).unwrap();
+
+        let count = 3;
+        let paths: Vec<_> = (0..count)
+            .map(|index| {
+                let path = std::env::temp_dir()
+                    .join(format!("e8-shard-{}-{index}.off", std::process::id()));
+                let shard_options = OffOptions {
+                    shard: Some(Shard { index, count }),
+                    ..options.clone()
+                };
+                let file = std::fs::File::create(&path).unwrap();
+                mirrors.write_off(file, &shard_options).unwrap();
+                path
+            })
+            .rev()
+            .collect();
+        let mut merged = Vec::new();
+        crate::shard::merge_shards(&paths, &mut merged).unwrap();
+        assert!(crate::shard::merge_shards(&paths[1..], Vec::new()).is_err());
+        for path in paths {
+            std::fs::remove_file(path).unwrap();
+        }
+        assert!(merged == full);
+    }
+
+    #[test]
+    fn shards_start_at_orbits() {
+        let mirrors = MirrorSet::A0;
+        let face_types = mirrors.face_types();
+        let point_sets = mirrors.point_sets(&face_types);
+        let runs = mirrors.element_runs(&face_types, 2);
+        let count = 7;
+        let mut starts = Vec::new();
+        let mut shares = 0.0;
+        for index in 0..count {
+            let (start, end, share) =
+                MirrorSet::shard_range(&runs, &point_sets, Shard { index, count }).unwrap();
+            let face_type = face_types[start.dim][start.face_type];
+            let face_set =
+                PointSet::new(mirrors.face_center(face_type).vertex_orbits().into_iter());
+            assert!(face_set.offsets.contains(&start.element), "{start:?}");
+            assert_eq!(end.is_none(), index + 1 == count);
+            starts.push(start);
+            shares += share;
+        }
+        // orbits of 26880 triangles leave some shards empty
+        assert!(starts.is_sorted() && starts[1] < starts[4]);
+        assert!((shares - 1.0).abs() < 1e-9);
+    }
+
+    /// Check that the subfaces on every line of a k-face section are in increasing order
+    fn assert_subfaces_sorted(off: &[u8]) {
+        let mut k_faces = false;
+        for line in std::str::from_utf8(off).unwrap().lines() {
+            if let Some(section) = line.strip_prefix("# ") {
+                k_faces = section.ends_with("-faces");
+            } else if k_faces && !line.is_empty() {
+                let indices: Vec<u64> = line
+                    .split(' ')
+                    .skip(1)
+                    .map(|word| word.trim_start_matches('-').parse().unwrap())
+                    .collect();
+                assert!(indices.is_sorted_by(|a, b| a < b), "{line}");
+            }
+        }
+    }
+
+    #[test]
+    fn write_off_subfaces_sorted() {
+        let options = OffOptions {
+            dim_limit: Some(4),
+            progress: ProgressMode::Quiet,
+            ..Default::default()
+        };
+        let mut off = Vec::new();
+        MirrorSet::A0.write_off(&mut off, &options).unwrap();
+        assert_subfaces_sorted(&off);
+    }
+
+    #[test]
+    fn write_off_oriented() {
+        // dimension 4 to have signs on both sides of the boundary of a boundary
+        let mirrors = MirrorSet::A0;
+        let options = OffOptions {
+            dim_limit: Some(4),
+            progress: ProgressMode::Quiet,
+            oriented: true,
+            ..Default::default()
+        };
+        let mut off = Vec::new();
+        mirrors.write_off(&mut off, &options).unwrap();
+        crate::orient::check_boundaries(&off[..]).unwrap();
+        assert_subfaces_sorted(&off);
+        assert!(off.len() as u64 <= mirrors.output_size(&options, Compression::None).unwrap());
+    }
+
+    #[test]
+    fn compressed_size_estimate_smaller() {
+        let mirrors = MirrorSet::A0;
+        let size = mirrors.off_size(None).unwrap();
+        let compressed = mirrors.compressed_size_estimate(Compression::Zstd).unwrap();
+        assert!(
+            0 < compressed && compressed < size / 2,
+            "{compressed} {size}"
+        );
+        assert_eq!(
+            mirrors.compressed_size_estimate(Compression::None).unwrap(),
+            size
+        );
+    }
+
+    #[test]
+    fn compression_ratio_of_layout_options() {
+        // few enough elements that the samples make up the whole .off
+        let mirrors = MirrorSet::A0;
+        let options = OffOptions {
+            dim_limit: Some(0),
+            progress: ProgressMode::Quiet,
+            coordinates: Coordinates::Unit(9),
+            ..Default::default()
+        };
+        let mut off = Vec::new();
+        mirrors.write_off(&mut off, &options).unwrap();
+        let layout = mirrors.off_layout(&options).unwrap();
+        assert_eq!(
+            layout.compression_ratio(Compression::Zstd).unwrap(),
+            Compression::Zstd.compressed_len(&off).unwrap() as f64 / off.len() as f64
+        );
+    }
+
+    #[test]
+    fn off_layout_dim_limit_past_8() {
+        let options = OffOptions {
+            dim_limit: Some(9),
+            ..Default::default()
+        };
+        assert!(matches!(
+            MirrorSet::A0.off_layout(&options),
+            Err(Error::Options(_))
+        ));
+    }
+
+    #[test]
+    fn off_layout_bad_shard() {
+        for (index, count) in [(2, 2), (0, 0)] {
+            let options = OffOptions {
+                shard: Some(Shard { index, count }),
+                ..Default::default()
+            };
+            assert!(matches!(
+                MirrorSet::A0.off_layout(&options),
+                Err(Error::Shard(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn digits_in_range_counts() {
+        for (start, end) in [(0, 0), (0, 1), (0, 10), (7, 12), (95, 1005), (0, 123456)] {
+            assert_eq!(
+                digits_in_range(start, end),
+                (start..end).map(num_length_u64).sum::<u64>(),
+                "{start}..{end}"
+            );
+        }
+    }
+
+    #[test]
+    fn off_size_exact() {
+        for (mirrors, dim_limit) in [
+            (MirrorSet::A0, 3),
+            (MirrorSet::C, 0),
+            (MirrorSet::B1, 0),
+            (MirrorSet::A0 | MirrorSet::C, 0),
+            (MirrorSet::B0, 1),
+        ] {
+            let mut off = Vec::new();
+            let options = OffOptions {
+                dim_limit: Some(dim_limit),
+                progress: ProgressMode::Quiet,
+                ..Default::default()
+            };
+            mirrors.write_off(&mut off, &options).unwrap();
+            assert_eq!(
+                mirrors.off_size(Some(dim_limit)).unwrap(),
+                off.len() as u64,
+                "{mirrors:?} {dim_limit}"
+            );
+
+            let options = OffOptions {
+                coordinates: Coordinates::Circumradius(4),
+                ..options
+            };
+            let mut off = Vec::new();
+            mirrors.write_off(&mut off, &options).unwrap();
+            assert_eq!(
+                mirrors.output_size(&options, Compression::None).unwrap(),
+                off.len() as u64,
+                "{mirrors:?} {dim_limit} circumradius"
+            );
+        }
+    }
 }
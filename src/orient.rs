@@ -0,0 +1,220 @@
+//! Orientations of the faces of an .off written with [`OffOptions::oriented`].
+//!
+//! A face of dimension 3 or more is oriented by the poles of the mirrors of its face type, and a
+//! polygon by its first three vertices, both carried from the face around
+//! [`MirrorSet::vertex`] to the face by the `E8` and `D8` its point set gives it. A face line
+//! lists a subface as `-i` instead of `i` when the outward normal of the subface followed by the
+//! orientation of the subface is opposite to the orientation of the face.
+//!
+//! [`OffOptions::oriented`]: crate::off::OffOptions::oriented
+//! [`MirrorSet::vertex`]: crate::e8::MirrorSet::vertex
+
+use crate::e8::E8;
+use crate::error::Error;
+use crate::error::Result;
+use crate::metric::Vec8i;
+use crate::metric::gcd;
+use crate::metric::widen;
+use crate::point::D8;
+use crate::point::Point;
+use crate::point::Vec8;
+use crate::volume::determinant;
+use fxhash::FxHashMap;
+use nalgebra::RowSVector;
+use nalgebra::SMatrix;
+use std::io::BufRead;
+
+pub(crate) type Row = RowSVector<f64, 8>;
+
+pub(crate) fn to_row(vec: Vec8) -> Row {
+    vec.map(|x| x as f64)
+}
+
+/// Matrix taking a row vector to its image under `e8` followed by `d8`
+pub(crate) fn frame(e8: E8, d8: D8) -> SMatrix<f64, 8, 8> {
+    // 4 times a unit vector is in the lattice, so it can go through `Point`
+    let mut frame = SMatrix::<f64, 8, 8>::zeros();
+    for i in 0..8 {
+        let mut unit = Vec8::zeros();
+        unit[i] = 4;
+        frame.set_row(i, &(to_row((Point::new(unit) * e8 * d8).vec()) / 4.0));
+    }
+    frame
+}
+
+/// Matrix taking a row vector to 4 times its image under `e8` followed by `d8`, in integers
+pub(crate) fn lattice_frame(e8: E8, d8: D8) -> SMatrix<i64, 8, 8> {
+    let mut frame = SMatrix::<i64, 8, 8>::zeros();
+    for i in 0..8 {
+        let mut unit = Vec8::zeros();
+        unit[i] = 4;
+        frame.set_row(i, &widen((Point::new(unit) * e8 * d8).vec()));
+    }
+    frame
+}
+
+/// `vec` divided by the gcd of its coordinates, which keeps its direction
+pub(crate) fn primitive(vec: Vec8i) -> Vec8i {
+    let g = vec
+        .iter()
+        .fold(0, |g, &x| gcd(g, x.unsigned_abs().into()))
+        .max(1);
+    vec / g as i64
+}
+
+/// Vector in the span of `basis` orthogonal to every vector of it but the `i`th, with positive
+/// dot product with that one, scaled to integers
+pub(crate) fn coweight(basis: &[Vec8i], i: usize) -> Vec8i {
+    // the `i`th column of the adjugate of the Gram matrix, which is its inverse times its
+    // positive determinant
+    let n = basis.len();
+    let gram: Vec<Vec<i128>> = (0..n)
+        .map(|a| (0..n).map(|b| basis[a].dot(&basis[b]).into()).collect())
+        .collect();
+    let cofactor = |j: usize| {
+        let minor = (0..n)
+            .filter(|&a| a != i)
+            .map(|a| (0..n).filter(|&b| b != j).map(|b| gram[a][b]).collect())
+            .collect();
+        let sign = if (i + j).is_multiple_of(2) { 1 } else { -1 };
+        sign * determinant(minor)
+    };
+    primitive((0..n).map(|j| basis[j] * cofactor(j) as i64).sum::<Vec8i>())
+}
+
+/// Whether `normal` followed by `subface` is oriented opposite to `face`, all bases of the same
+/// subspace up to components in the span of `subface`
+pub(crate) fn is_reversed(normal: Vec8i, subface: &[Vec8i], face: &[Vec8i]) -> bool {
+    let rows: Vec<_> = [normal]
+        .into_iter()
+        .chain(subface.iter().copied())
+        .collect();
+    let matrix = rows
+        .iter()
+        .map(|row| face.iter().map(|b| row.dot(b).into()).collect())
+        .collect();
+    determinant(matrix) < 0
+}
+
+/// Check that the boundary of the boundary of every face in an oriented .off is zero, taking
+/// polygons to be bounded by the edges between consecutive vertices
+pub fn check_boundaries(reader: impl BufRead) -> Result<()> {
+    let bad = |msg: String| Err(Error::Orientation(msg));
+    // faces of the previous section, as signed subfaces or, for polygons, vertex cycles
+    let mut previous: Vec<Vec<(u64, i64)>> = Vec::new();
+    let mut current = Vec::new();
+    let mut dim = 0;
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if let Some(comment) = line.strip_prefix("# ") {
+            previous = std::mem::take(&mut current);
+            dim = match comment {
+                "Vertices" => 0,
+                "Faces" => 2,
+                _ => match comment.strip_suffix("-faces").map(str::parse) {
+                    Some(Ok(dim)) => dim,
+                    _ => return bad(format!("unknown section {comment:?}")),
+                },
+            };
+            continue;
+        }
+        if dim < 2 || line.is_empty() {
+            continue;
+        }
+
+        let mut words = line.split_whitespace();
+        let count = words.next().and_then(|count| count.parse::<usize>().ok());
+        let mut face = Vec::new();
+        for word in words {
+            let (sign, index) = match word.strip_prefix('-') {
+                Some(index) => (-1, index),
+                None => (1, word),
+            };
+            match index.parse::<u64>() {
+                Ok(index) => face.push((index, sign)),
+                Err(_) => return bad(format!("bad index {word:?} on line {}", number + 1)),
+            }
+        }
+        if count != Some(face.len()) {
+            return bad(format!("wrong count on line {}", number + 1));
+        }
+
+        if dim >= 3 {
+            // edges by their two vertices below 3-faces, and faces by their index otherwise
+            let mut boundary: FxHashMap<(u64, u64), i64> = FxHashMap::default();
+            for &(subface, sign) in &face {
+                let Some(subsubfaces) = previous.get(subface as usize) else {
+                    return bad(format!("no face {subface} on line {}", number + 1));
+                };
+                if dim == 3 {
+                    for (i, &(a, _)) in subsubfaces.iter().enumerate() {
+                        let (b, _) = subsubfaces[(i + 1) % subsubfaces.len()];
+                        let edge_sign = if a < b { sign } else { -sign };
+                        *boundary.entry((a.min(b), a.max(b))).or_default() += edge_sign;
+                    }
+                } else {
+                    for &(subsubface, subsign) in subsubfaces {
+                        *boundary.entry((subsubface, 0)).or_default() += sign * subsign;
+                    }
+                }
+            }
+            if boundary.values().any(|&sum| sum != 0) {
+                return bad(format!(
+                    "boundary of the boundary of the face on line {} is not zero",
+                    number + 1
+                ));
+            }
+        }
+        current.push(face);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::e8::Mirror;
+
+    #[test]
+    fn frame_matches_points() {
+        let e8 = Mirror::A2.mat() * Mirror::B0.mat() * Mirror::C.mat();
+        let point = Point::new([1, 3, -1, 1, 1, 1, -1, 3].into());
+        let d8 = point.element;
+        let image = to_row((point * e8 * d8).vec());
+        assert_eq!(to_row(point.vec()) * frame(e8, d8), image);
+    }
+
+    #[test]
+    fn exact_coweights_and_orientations() {
+        let poles: Vec<_> = [Mirror::A0, Mirror::A1, Mirror::B0]
+            .map(|m| widen(m.pole()))
+            .into();
+        for i in 0..3 {
+            let coweight = coweight(&poles, i);
+            for (j, pole) in poles.iter().enumerate() {
+                assert_eq!(coweight.dot(pole) > 0, i == j);
+                assert!(coweight.dot(pole) >= 0);
+            }
+        }
+        // the first dot product is zero, so the elimination has to swap rows
+        let [a, b, c] = [poles[0], poles[1], poles[2]];
+        assert!(!is_reversed(c, &[a, b], &[a, b, c]));
+        assert!(is_reversed(c, &[b, a], &[a, b, c]));
+        assert!(is_reversed(-c, &[a, b], &[a, b, c]));
+    }
+
+    #[test]
+    fn check_boundaries_square() {
+        // a square pyramid, missing its base for the second check and with it reversed for the
+        // third
+        let header = "8OFF\n5 5 8 1 \n\n# Vertices\n";
+        let vertices = "0 0 0 0 0 0 0 0\n".repeat(5);
+        let faces = "\n# Faces\n4 0 1 2 3\n3 0 4 1\n3 1 4 2\n3 2 4 3\n3 3 4 0\n";
+        let off = format!("{header}{vertices}{faces}\n# 3-faces\n5 0 1 2 3 4\n\n");
+        check_boundaries(off.as_bytes()).unwrap();
+        let off = format!("{header}{vertices}{faces}\n# 3-faces\n4 1 2 3 4\n\n");
+        assert!(check_boundaries(off.as_bytes()).is_err());
+        let off = format!("{header}{vertices}{faces}\n# 3-faces\n5 -0 1 2 3 4\n\n");
+        assert!(check_boundaries(off.as_bytes()).is_err());
+    }
+}
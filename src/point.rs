@@ -1,17 +1,32 @@
-use crate::combs::COMBS_INDS;
 use crate::combs::COMBS_LENS;
-use crate::combs::COMBS_LISTS;
+use crate::combs::MAX_DIM;
+use crate::combs::comb_at;
+use crate::combs::comb_index;
+use fxhash::FxHashMap;
 use nalgebra::RowSVector;
+use std::fmt::Debug;
+use std::hash::Hash;
 use std::iter::once;
+use std::marker::PhantomData;
 use std::ops::Mul;
 
-pub type Vec8 = RowSVector<i16, 8>;
+/// Scalar type of coordinates, `i16` unless widened by the `i32` or `i64` feature
+#[cfg(not(any(feature = "i32", feature = "i64")))]
+pub type Coord = i16;
+#[cfg(all(feature = "i32", not(feature = "i64")))]
+pub type Coord = i32;
+#[cfg(feature = "i64")]
+pub type Coord = i64;
+
+/// Vector of `N` coordinates, at most 16 of them for orbits
+pub type VecN<const N: usize> = RowSVector<Coord, N>;
+pub type Vec8 = VecN<8>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct AxSign(i8);
 
 impl AxSign {
-    pub fn new(ax: usize, sign: i16) -> Self {
+    pub fn new(ax: usize, sign: Coord) -> Self {
         if sign >= 0 {
             Self(ax as i8)
         } else {
@@ -27,7 +42,7 @@ impl AxSign {
         }
     }
 
-    pub fn sign(self) -> i16 {
+    pub fn sign(self) -> Coord {
         if self.0 >= 0 { 1 } else { -1 }
     }
 
@@ -36,26 +51,77 @@ impl AxSign {
     }
 }
 
+/// Group of signed permutations of `N` coordinates, whose orbits are ranked, unranked and
+/// iterated the same way whichever it is
+///
+/// Orbits have their coordinates sorted, by absolute value if the group changes signs. The
+/// element of a point takes its orbit's representative to it, keeping coordinates that are equal
+/// in the representative in order and zeros positive.
+pub trait Symmetry<const N: usize>:
+    Debug + Clone + Copy + PartialEq + Eq + Hash + Mul<Output = Self>
+{
+    /// Whether the group changes the signs of coordinates
+    const SIGNS: bool;
+    /// Whether it only changes an even number of them, the rest going into [`Orbit::sign`]
+    const EVEN: bool;
+
+    /// Element with these axes and signs, which must be in the group
+    fn from_axes(axs: [AxSign; N]) -> Self;
+
+    fn axes(self) -> [AxSign; N];
+
+    fn identity() -> Self {
+        Self::from_axes(std::array::from_fn(|i| AxSign(i as i8)))
+    }
+
+    fn inv(self) -> Self {
+        Self::from_axes(invert(self.axes()))
+    }
+
+    /// Whether the coordinate at position `i` of `rep` takes a sign of its own
+    fn signed(rep: VecN<N>, i: usize) -> bool {
+        Self::SIGNS && rep[i] != 0 && (i != 0 || !Self::EVEN)
+    }
+}
+
+fn invert<const N: usize>(axs: [AxSign; N]) -> [AxSign; N] {
+    let mut inv = [AxSign(-128); N];
+    for (i, p) in axs.iter().enumerate() {
+        inv[p.ax()] = AxSign::new(i, p.sign());
+    }
+    inv
+}
+
+fn compose<const N: usize>(first: [AxSign; N], second: [AxSign; N]) -> [AxSign; N] {
+    second.map(|p| {
+        let first_p = first[p.ax()];
+        AxSign::new(first_p.ax(), p.sign() * first_p.sign())
+    })
+}
+
+fn apply<const N: usize>(vec: VecN<N>, axs: [AxSign; N]) -> VecN<N> {
+    VecN::from_fn(|_, i| vec[axs[i].ax()] * axs[i].sign())
+}
+
+/// Element of the group of even signed permutations of `N` coordinates, the Weyl group of D_N
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct D8([AxSign; 8]);
+pub struct Dn<const N: usize>([AxSign; N]);
 
-impl D8 {
-    pub fn new(axs: [AxSign; 8]) -> Self {
+pub type D8 = Dn<8>;
+
+impl<const N: usize> Dn<N> {
+    pub fn new(axs: [AxSign; N]) -> Self {
         let new = Self(axs);
         assert!(new.signs_even(), "{:?}", axs);
         new
     }
 
     pub fn identity() -> Self {
-        Self([0, 1, 2, 3, 4, 5, 6, 7].map(AxSign))
+        Self(std::array::from_fn(|i| AxSign(i as i8)))
     }
 
     pub fn inv(self) -> Self {
-        let mut inv = [AxSign(-128); 8];
-        for (i, p) in self.0.iter().enumerate() {
-            inv[p.ax()] = AxSign::new(i, p.sign());
-        }
-        Self(inv)
+        Self(invert(self.0))
     }
 
     pub fn signs_even(self) -> bool {
@@ -63,54 +129,154 @@ impl D8 {
     }
 }
 
-impl Mul<D8> for Vec8 {
+impl D8 {
+    /// The axis and sign of every coordinate in four bits each, the sign in the highest
+    pub fn pack(self) -> u32 {
+        self.0
+            .iter()
+            .rev()
+            .fold(0, |packed, p| packed << 4 | (p.0 as u32 & 0xf))
+    }
+
+    pub fn unpack(packed: u32) -> Self {
+        // !ax is -1 - ax, which is 15 - ax in four bits
+        Self(std::array::from_fn(|i| {
+            let nibble = (packed >> (4 * i) & 0xf) as i8;
+            AxSign(if nibble < 8 { nibble } else { nibble - 16 })
+        }))
+    }
+}
+
+impl<const N: usize> Symmetry<N> for Dn<N> {
+    const SIGNS: bool = true;
+    const EVEN: bool = true;
+
+    fn from_axes(axs: [AxSign; N]) -> Self {
+        Self::new(axs)
+    }
+
+    fn axes(self) -> [AxSign; N] {
+        self.0
+    }
+}
+
+/// Element of the hyperoctahedral group, with any sign changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bn<const N: usize>([AxSign; N]);
+
+pub type B8 = Bn<8>;
+
+impl<const N: usize> Symmetry<N> for Bn<N> {
+    const SIGNS: bool = true;
+    const EVEN: bool = false;
+
+    fn from_axes(axs: [AxSign; N]) -> Self {
+        Self(axs)
+    }
+
+    fn axes(self) -> [AxSign; N] {
+        self.0
+    }
+}
+
+/// Permutation of the coordinates, without sign changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sn<const N: usize>([AxSign; N]);
+
+pub type S8 = Sn<8>;
+
+impl<const N: usize> Symmetry<N> for Sn<N> {
+    const SIGNS: bool = false;
+    const EVEN: bool = false;
+
+    fn from_axes(axs: [AxSign; N]) -> Self {
+        assert!(axs.iter().all(|p| p.sign() == 1), "{:?}", axs);
+        Self(axs)
+    }
+
+    fn axes(self) -> [AxSign; N] {
+        self.0
+    }
+}
+
+impl<const N: usize> Mul<Dn<N>> for VecN<N> {
     type Output = Self;
-    fn mul(self, d8: D8) -> Self {
-        d8.0.map(|p| self[p.ax()] * p.sign()).into()
+    fn mul(self, dn: Dn<N>) -> Self {
+        apply(self, dn.0)
     }
 }
 
-impl Mul for D8 {
+impl<const N: usize> Mul<Bn<N>> for VecN<N> {
+    type Output = Self;
+    fn mul(self, bn: Bn<N>) -> Self {
+        apply(self, bn.0)
+    }
+}
+
+impl<const N: usize> Mul<Sn<N>> for VecN<N> {
+    type Output = Self;
+    fn mul(self, sn: Sn<N>) -> Self {
+        apply(self, sn.0)
+    }
+}
+
+impl<const N: usize> Mul for Dn<N> {
     type Output = Self;
     fn mul(self, other: Self) -> Self {
-        Self(other.0.map(|p| {
-            let self_p = self.0[p.ax()];
-            AxSign::new(self_p.ax(), p.sign() * self_p.sign())
-        }))
+        Self(compose(self.0, other.0))
     }
 }
 
+impl<const N: usize> Mul for Bn<N> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self(compose(self.0, other.0))
+    }
+}
+
+impl<const N: usize> Mul for Sn<N> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self(compose(self.0, other.0))
+    }
+}
+
+/// Orbit of a group of signed permutations of `N` coordinates, 8 and D_N unless given
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Orbit {
-    pub rep: Vec8,
-    pub sign: i16,
+pub struct Orbit<const N: usize = 8, G = Dn<N>> {
+    pub rep: VecN<N>,
+    /// Sign of the product of the coordinates, which the representative's first one takes, for
+    /// groups with only even sign changes, and 1 for the others
+    pub sign: Coord,
+    group: PhantomData<G>,
 }
 
 impl Orbit {
-    pub fn size(self) -> u64 {
-        let mut size = 1;
-        let mut group = 1;
-        for (i, (&x, y)) in self
-            .rep
-            .iter()
-            .zip(self.rep.iter().skip(1).map(Some).chain(once(None)))
-            .enumerate()
-        {
-            if Some(&x) == y {
-                group += 1;
-            } else {
-                size *= COMBS_LENS[i + 1][group];
-                group = 1;
-            }
-            if x != 0 && i != 0 {
-                size *= 2;
-            }
+    pub fn new(rep: Vec8, sign: Coord) -> Self {
+        Self::of(rep, sign)
+    }
+}
+
+impl<const N: usize, G: Symmetry<N>> Orbit<N, G> {
+    /// Orbit under `G` with the sorted representative `rep`
+    pub fn of(rep: VecN<N>, sign: Coord) -> Self {
+        const { assert!(N <= MAX_DIM) };
+        Self {
+            rep,
+            sign,
+            group: PhantomData,
         }
-        size
     }
 
-    pub fn iter(self) -> impl Iterator<Item = Point> {
-        let mut bases = Vec::new();
+    pub fn size(self) -> u64 {
+        let (runs, signs) = self.runs();
+        runs.iter().map(|(_, _, p)| p).product::<u64>() << signs
+    }
+
+    /// Runs of equal coordinates, last first, as the coordinates up to the end of the run, its
+    /// length and its number of placements, with the number of coordinates that take a sign
+    fn runs(self) -> (Vec<(usize, usize, u64)>, u32) {
+        let mut runs = Vec::new();
         let mut group = 1;
         let mut signs = 0;
         for (i, (&x, y)) in self
@@ -122,110 +288,132 @@ impl Orbit {
             if Some(&x) == y {
                 group += 1;
             } else {
-                bases.push((i + 1, group, COMBS_LENS[i + 1][group]));
+                runs.push((i + 1, group, COMBS_LENS[i + 1][group]));
                 group = 1;
             }
-            if x != 0 && i != 0 {
+            if G::signed(self.rep, i) {
                 signs += 1;
             }
         }
-        bases.reverse();
-        let bases = bases;
-
-        let perms = bases.iter().map(|(_, _, p)| p).product();
-
-        (0..perms)
-            .map(move |mut i| {
-                let mut d8 = [None; 8];
-                for (n, k, p) in &bases {
-                    let comb_num = COMBS_LISTS[*n][*k][(i % p) as usize];
-                    i /= p;
-
-                    let none_iter = d8.iter_mut().filter(|p| p.is_none());
-                    for (j, (_, p)) in none_iter
-                        .enumerate()
-                        .filter(|(b, _)| (comb_num >> (n - 1 - b)) & 1 == 1)
-                        .enumerate()
-                    {
-                        *p = Some(AxSign::new(n - k + j, 1)) // placeholder sign
-                    }
-                }
+        runs.reverse();
+        (runs, signs)
+    }
+
+    /// Axes of the points of the placement `i`, with placeholder signs
+    fn placement(runs: &[(usize, usize, u64)], mut i: u64) -> [AxSign; N] {
+        let mut d8 = [None; N];
+        for (n, k, p) in runs {
+            let comb_num = comb_at(*n, *k, i % p);
+            i /= p;
+
+            let none_iter = d8.iter_mut().filter(|p| p.is_none());
+            for (j, (_, p)) in none_iter
+                .enumerate()
+                .filter(|(b, _)| (comb_num >> (n - 1 - b)) & 1 == 1)
+                .enumerate()
+            {
+                *p = Some(AxSign::new(n - k + j, 1)) // placeholder sign
+            }
+        }
+        d8.map(Option::unwrap)
+    }
 
-                let d8 = d8.map(Option::unwrap);
+    /// Point of the placement `d8` with the signs `s`
+    fn signed(self, mut d8: [AxSign; N], s: u32) -> Point<N, G> {
+        for p in d8.iter_mut() {
+            if (s >> (N - 1 - p.ax())) & 1 == 1
+                || (G::EVEN && p.ax() == 0 && !s.count_ones().is_multiple_of(2))
+            {
+                p.flip_sign();
+            }
+        }
+        Point {
+            orbit: self,
+            element: G::from_axes(d8),
+        }
+    }
 
-                (0u8..1 << signs).map(move |s| {
-                    let mut d8 = d8;
-                    for p in d8.iter_mut() {
-                        if (s >> (7 - p.ax())) & 1 == 1 || (p.ax() == 0 && s.count_ones() % 2 != 0)
-                        {
-                            p.flip_sign();
-                        }
-                    }
-                    Point {
-                        orbit: self,
-                        d8: D8::new(d8),
-                    }
-                })
-            })
-            .flatten()
+    pub fn iter(self) -> impl Iterator<Item = Point<N, G>> {
+        let (runs, signs) = self.runs();
+        let perms = runs.iter().map(|(_, _, p)| p).product();
+
+        (0..perms).flat_map(move |i| {
+            let d8 = Self::placement(&runs, i);
+            (0u32..1 << signs).map(move |s| self.signed(d8, s))
+        })
+    }
+
+    /// Point at `index` in [`iter`](Self::iter), the one whose [`Point::orbit_index`] is `index`,
+    /// or `None` past the end of the orbit
+    pub fn nth(self, index: u64) -> Option<Point<N, G>> {
+        if index >= self.size() {
+            return None;
+        }
+        let (runs, signs) = self.runs();
+        let d8 = Self::placement(&runs, index >> signs);
+        Some(self.signed(d8, (index & ((1 << signs) - 1)) as u32))
     }
 }
 
-pub fn opt_bits_to_num(bits: [Option<bool>; 8]) -> u8 {
+fn opt_bits_to_num<const N: usize>(bits: [Option<bool>; N]) -> u32 {
     let mut num = 0;
-    for bit in bits {
-        if let Some(bit) = bit {
-            num <<= 1;
-            num |= if bit { 1 } else { 0 };
-        }
+    for bit in bits.into_iter().flatten() {
+        num <<= 1;
+        num |= if bit { 1 } else { 0 };
     }
     num
 }
 
+/// Point as its orbit under a group of signed permutations of `N` coordinates, 8 and D_N unless
+/// given, and the element of the group taking the orbit's representative to it
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Point {
-    pub orbit: Orbit,
-    pub d8: D8,
+pub struct Point<const N: usize = 8, G = Dn<N>> {
+    pub orbit: Orbit<N, G>,
+    pub element: G,
 }
 
 impl Point {
     pub fn new(vec: Vec8) -> Self {
-        let sign = vec.iter().map(|x| x.signum()).product();
-        let sign_pm1: i16 = vec
-            .iter()
-            .map(|x| if x.signum() == 0 { 1 } else { x.signum() })
-            .product();
-        let mut deco = [
-            (vec[0].abs(), AxSign::new(0, vec[0].signum())),
-            (vec[1].abs(), AxSign::new(1, vec[1].signum())),
-            (vec[2].abs(), AxSign::new(2, vec[2].signum())),
-            (vec[3].abs(), AxSign::new(3, vec[3].signum())),
-            (vec[4].abs(), AxSign::new(4, vec[4].signum())),
-            (vec[5].abs(), AxSign::new(5, vec[5].signum())),
-            (vec[6].abs(), AxSign::new(6, vec[6].signum())),
-            (vec[7].abs(), AxSign::new(7, vec[7].signum())),
-        ]; // why won't they stabilize zip
+        Self::from_vec(vec)
+    }
+}
+
+impl<const N: usize, G: Symmetry<N>> Point<N, G> {
+    /// Point at `vec` with its orbit under `G`
+    pub fn from_vec(vec: VecN<N>) -> Self {
+        const { assert!(N <= MAX_DIM) };
+        let key = |x: Coord| if G::SIGNS { x.abs() } else { x };
+        let sign_of = |x: Coord| if G::SIGNS { x.signum() } else { 1 };
+        let mut deco: [_; N] =
+            std::array::from_fn(|i| (key(vec[i]), AxSign::new(i, sign_of(vec[i]))));
         deco.sort_by_key(|(x, _)| *x);
-        let rep = deco.map(|(x, _)| x).into();
+        let rep = VecN::from_fn(|_, i| deco[i].0);
         let mut d8_inner = deco.map(|(_, p)| p);
-        if sign_pm1 == -1 {
-            d8_inner[0].flip_sign();
-        }
-        // eprintln!("{:?}", vec);
-        let d8 = D8::new(d8_inner);
+        let sign = if G::EVEN {
+            let sign_pm1: Coord = vec
+                .iter()
+                .map(|x| if x.signum() == 0 { 1 } else { x.signum() })
+                .product();
+            if sign_pm1 == -1 {
+                d8_inner[0].flip_sign();
+            }
+            vec.iter().map(|x| x.signum()).product()
+        } else {
+            1
+        };
         Self {
-            orbit: Orbit { rep, sign },
-            d8: d8.inv(),
+            orbit: Orbit::of(rep, sign),
+            element: G::from_axes(invert(d8_inner)),
         }
     }
 
-    pub fn vec(self) -> Vec8 {
+    pub fn vec(self) -> VecN<N> {
         let mut rep = self.orbit.rep;
         rep[0] *= self.orbit.sign;
-        rep * self.d8
+        apply(rep, self.element.axes())
     }
 
-    pub fn dot(self, other: Self) -> i16 {
+    pub fn dot(self, other: Self) -> Coord {
         self.vec().dot(&other.vec())
     }
 
@@ -238,6 +426,7 @@ impl Point {
 
         let mut group = 1;
         let mut signs = 0;
+        let axes = self.element.axes();
         for (i, (&x, y)) in self
             .orbit
             .rep
@@ -248,7 +437,7 @@ impl Point {
             if Some(&x) == y {
                 group += 1;
             } else {
-                let comb_num = opt_bits_to_num(self.d8.0.map(|p| {
+                let comb_num = opt_bits_to_num(axes.map(|p| {
                     if p.ax() > i {
                         None
                     } else {
@@ -256,18 +445,18 @@ impl Point {
                     }
                 }));
                 index *= COMBS_LENS[i + 1][group];
-                index += COMBS_INDS[i + 1][comb_num as usize];
+                index += comb_index::<N>(comb_num);
                 group = 1;
             }
-            if x != 0 && i != 0 {
+            if G::signed(self.orbit.rep, i) {
                 signs += 1;
             }
         }
 
         index <<= signs;
-        for p in self.d8.0 {
-            if p.ax() != 0 && self.orbit.rep[p.ax()] != 0 && p.sign() == -1 {
-                index |= 1 << (7 - p.ax())
+        for p in axes {
+            if G::signed(self.orbit.rep, p.ax()) && p.sign() == -1 {
+                index |= 1 << (N - 1 - p.ax())
             }
         }
 
@@ -277,28 +466,120 @@ impl Point {
     pub fn representative(self) -> Self {
         Self {
             orbit: self.orbit,
-            d8: D8::identity(),
+            element: G::identity(),
         }
     }
 }
 
-impl Mul<D8> for Point {
+/// A point as the id of its orbit in an [`OrbitTable`] and its packed `D8`, in 8 bytes for the
+/// hash sets of big orbits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedPoint(u64);
+
+impl PackedPoint {
+    pub fn orbit_id(self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+
+    pub fn d8(self) -> D8 {
+        D8::unpack(self.0 as u32)
+    }
+}
+
+/// Ids of the orbits of the points packed so far
+#[derive(Debug, Clone, Default)]
+pub struct OrbitTable {
+    orbits: Vec<Orbit>,
+    ids: FxHashMap<Orbit, u32>,
+}
+
+impl OrbitTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of orbits with an id
+    pub fn len(&self) -> usize {
+        self.orbits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orbits.is_empty()
+    }
+
+    /// Id of `orbit`, giving it the next one if it has none yet
+    pub fn id(&mut self, orbit: Orbit) -> u32 {
+        *self.ids.entry(orbit).or_insert_with(|| {
+            self.orbits.push(orbit);
+            (self.orbits.len() - 1) as u32
+        })
+    }
+
+    pub fn pack(&mut self, point: Point) -> PackedPoint {
+        PackedPoint((self.id(point.orbit) as u64) << 32 | point.element.pack() as u64)
+    }
+
+    /// Point packed by this table
+    pub fn unpack(&self, packed: PackedPoint) -> Point {
+        Point {
+            orbit: self.orbits[packed.orbit_id() as usize],
+            element: packed.d8(),
+        }
+    }
+}
+
+impl<const N: usize, G: Symmetry<N>> Mul<G> for Point<N, G> {
     type Output = Self;
-    fn mul(self, d8: D8) -> Self {
-        // TODO: don't roundtrip through vec
-        Self::new(
-            Self {
-                orbit: self.orbit,
-                d8: self.d8 * d8,
+    /// Same as `Point::from_vec(self.vec() * g)`, without sorting
+    fn mul(self, g: G) -> Self {
+        let orbit = self.orbit;
+        let rep = orbit.rep;
+        let element = self.element * g;
+        // with distinct coordinates, none of them a zero that could take either sign, only one
+        // element takes the representative to the point
+        if (rep[0] != 0 || !G::SIGNS) && (1..N).all(|i| rep[i - 1] != rep[i]) {
+            return Self { orbit, element };
+        }
+
+        // otherwise take the one `from_vec` does: axes in order within each run of equal
+        // coordinates, positive signs on zeros, and the parity fixed on the axis of rep[0]
+        let mut next = [0; N];
+        for i in 1..N {
+            next[i] = if rep[i] == rep[i - 1] { next[i - 1] } else { i };
+        }
+        let starts = next;
+        let mut canonical = element.axes();
+        let mut odd = false;
+        for p in &mut canonical {
+            let ax = p.ax();
+            let sign = match rep[ax] {
+                0 => 1,
+                _ if ax == 0 => p.sign() * orbit.sign,
+                _ => p.sign(),
+            };
+            odd ^= sign == -1;
+            *p = AxSign::new(next[starts[ax]], sign);
+            next[starts[ax]] += 1;
+        }
+        if G::EVEN && odd {
+            for p in &mut canonical {
+                if p.ax() == 0 {
+                    p.flip_sign();
+                }
             }
-            .vec(),
-        )
+        }
+        Self {
+            orbit,
+            element: G::from_axes(canonical),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::prop;
+    use rand::Rng;
 
     #[test]
     fn d8_order() {
@@ -327,44 +608,22 @@ mod tests {
     #[test]
     fn d8_orbit_size() {
         assert_eq!(
-            Orbit {
-                rep: [1, 2, 3, 4, 5, 6, 7, 8].into(),
-                sign: 1
-            }
-            .size(),
+            Orbit::new([1, 2, 3, 4, 5, 6, 7, 8].into(), 1).size(),
             128 * 40320
         );
         assert_eq!(
-            Orbit {
-                rep: [0, 2, 3, 4, 5, 6, 7, 8].into(),
-                sign: 1
-            }
-            .size(),
+            Orbit::new([0, 2, 3, 4, 5, 6, 7, 8].into(), 1).size(),
             128 * 40320
         );
         assert_eq!(
-            Orbit {
-                rep: [0, 0, 3, 4, 5, 6, 7, 8].into(),
-                sign: 1
-            }
-            .size(),
+            Orbit::new([0, 0, 3, 4, 5, 6, 7, 8].into(), 1).size(),
             64 * 40320 / 2
         );
-        assert_eq!(
-            Orbit {
-                rep: [1, 1, 1, 1, 1, 1, 1, 1].into(),
-                sign: 1
-            }
-            .size(),
-            128
-        );
+        assert_eq!(Orbit::new([1, 1, 1, 1, 1, 1, 1, 1].into(), 1).size(), 128);
     }
 
-    fn d8_orbit_index_consistent(rep: [i16; 8]) {
-        let orbit = Orbit {
-            rep: rep.into(),
-            sign: 1,
-        };
+    fn d8_orbit_index_consistent(rep: [Coord; 8]) {
+        let orbit = Orbit::new(rep.into(), 1);
         for (i, point) in orbit.iter().enumerate() {
             if i % 100000 == 0 {
                 println!("{i}")
@@ -432,6 +691,173 @@ mod tests {
     #[test]
     fn point_d8_even() {
         let point = Point::new([0, 0, 0, 0, 0, 0, -2, 2].into());
-        assert!(point.d8.signs_even());
+        assert!(point.element.signs_even());
+    }
+
+    #[test]
+    fn prop_point_roundtrip() {
+        prop::check(1000, |rng| {
+            let v = prop::small_vec(rng, 20);
+            let point = Point::new(v);
+            assert_eq!(point.vec(), v, "{:?}", point);
+            assert!(point.element.signs_even());
+        });
+    }
+
+    #[test]
+    fn prop_d8_mul_associative() {
+        prop::check(1000, |rng| {
+            let (a, b, c) = (prop::d8(rng), prop::d8(rng), prop::d8(rng));
+            assert_eq!((a * b) * c, a * (b * c));
+            assert_eq!(a * a.inv(), D8::identity());
+            let v = prop::small_vec(rng, 20);
+            assert_eq!(v * (a * b), (v * a) * b);
+        });
+    }
+
+    #[test]
+    fn prop_point_mul_d8() {
+        prop::check(1000, |rng| {
+            let v = prop::small_vec(rng, 4);
+            let d8 = prop::d8(rng);
+            assert_eq!((Point::new(v) * d8).vec(), v * d8);
+            assert_eq!(Point::new(v) * d8, Point::new(v * d8));
+            // repeated coordinates without zeros, with either sign on the smallest
+            let v = prop::small_vec(rng, 2).map(|x| 2 * x + 1);
+            assert_eq!(Point::new(v) * d8, Point::new(v * d8));
+            let v = prop::small_vec(rng, 20);
+            assert_eq!(Point::new(v) * d8, Point::new(v * d8));
+        });
+    }
+
+    #[test]
+    fn prop_orbit_nth() {
+        prop::check(1000, |rng| {
+            let point = Point::new(prop::small_vec(rng, 3));
+            assert_eq!(point.orbit.nth(point.orbit_index()), Some(point));
+        });
+        let orbit = Point::new([0, 0, 1, 2, 2, 3, 3, 3].into()).orbit;
+        for (i, point) in orbit.iter().enumerate().step_by(97) {
+            assert_eq!(orbit.nth(i as u64), Some(point));
+        }
+    }
+
+    /// Check that iterating, ranking and unranking agree on all of `orbit`
+    fn check_orbit<const N: usize, G: Symmetry<N>>(orbit: Orbit<N, G>) {
+        let mut vecs = std::collections::HashSet::new();
+        for (i, point) in orbit.iter().enumerate() {
+            assert_eq!(point.orbit_index(), i as u64, "{}", point.vec());
+            assert_eq!(orbit.nth(i as u64), Some(point));
+            assert_eq!(Point::<N, G>::from_vec(point.vec()), point);
+            assert!(vecs.insert(point.vec()), "{} repeated", point.vec());
+        }
+        assert_eq!(vecs.len() as u64, orbit.size());
+    }
+
+    #[test]
+    fn b8_and_s8_orbits() {
+        let distinct: Vec8 = [1, 2, 3, 4, 5, 6, 7, 8].into();
+        assert_eq!(Point::<8, B8>::from_vec(distinct).orbit_size(), 256 * 40320);
+        assert_eq!(Point::<8, S8>::from_vec(distinct).orbit_size(), 40320);
+        let cube: Vec8 = [1, -1, 1, 1, 1, 1, 1, 1].into();
+        assert_eq!(Point::<8, B8>::from_vec(cube).orbit.sign, 1);
+        assert_eq!(Point::<8, B8>::from_vec(cube).orbit_size(), 256);
+        check_orbit(Point::<8, B8>::from_vec(cube).orbit);
+        check_orbit(Point::<8, B8>::from_vec([0, 0, 1, 2, 2, 0, 3, 3].into()).orbit);
+        check_orbit(Point::<8, S8>::from_vec([0, 0, 0, 0, 1, 1, 1, 1].into()).orbit);
+        check_orbit(Point::<8, S8>::from_vec([-2, 0, 1, 1, 1, 3, -2, 3].into()).orbit);
+    }
+
+    #[test]
+    fn prop_b8_and_s8_points() {
+        prop::check(1000, |rng| {
+            let v = prop::small_vec(rng, 3);
+            let (b8, s8) = (prop::b8(rng), prop::s8(rng));
+            let point = Point::<8, B8>::from_vec(v);
+            assert_eq!(point.vec(), v);
+            assert_eq!(point.orbit.nth(point.orbit_index()), Some(point));
+            assert_eq!(point * b8, Point::from_vec(v * b8));
+            let point = Point::<8, S8>::from_vec(v);
+            assert_eq!(point.vec(), v);
+            assert_eq!(point.orbit.nth(point.orbit_index()), Some(point));
+            assert_eq!(point * s8, Point::from_vec(v * s8));
+        });
+    }
+
+    #[test]
+    fn d4_orbits() {
+        let distinct = Point::<4>::from_vec([1, -2, 3, 4].into());
+        assert_eq!(distinct.orbit_size(), 8 * 24);
+        check_orbit(distinct.orbit);
+        check_orbit(Point::<4>::from_vec([0, 1, -1, 1].into()).orbit);
+        check_orbit(Point::<4>::from_vec([2, 0, 2, 0].into()).orbit);
+        check_orbit(Point::<4, Bn<4>>::from_vec([1, -1, 1, 1].into()).orbit);
+        check_orbit(Point::<4, Sn<4>>::from_vec([3, 1, 3, 0].into()).orbit);
+    }
+
+    #[test]
+    fn prop_d16_points() {
+        let distinct: VecN<16> = VecN::from_fn(|_, i| i as Coord + 1);
+        assert_eq!(
+            Point::<16>::from_vec(distinct).orbit_size(),
+            (1 << 15) * (1..=16).product::<u64>()
+        );
+        prop::check(1000, |rng| {
+            let v: VecN<16> = VecN::from_fn(|_, _| rng.random_range(-3..=3));
+            let point = Point::<16>::from_vec(v);
+            assert_eq!(point.vec(), v);
+            let index = point.orbit_index();
+            assert!(index < point.orbit_size());
+            assert_eq!(point.orbit.nth(index), Some(point));
+            let other = point
+                .orbit
+                .nth(rng.random_range(0..point.orbit_size()))
+                .unwrap();
+            assert_eq!(Point::<16>::from_vec(other.vec()), other);
+        });
+    }
+
+    #[test]
+    fn prop_pack_roundtrip() {
+        assert_eq!(size_of::<PackedPoint>(), 8);
+        let mut table = OrbitTable::new();
+        let mut packed = Vec::new();
+        prop::check(1000, |rng| {
+            let d8 = prop::d8(rng);
+            assert_eq!(D8::unpack(d8.pack()), d8);
+            let point = Point::new(prop::small_vec(rng, 2));
+            packed.push((table.pack(point), point));
+        });
+        for (packed, point) in packed {
+            assert_eq!(table.unpack(packed), point);
+            assert_eq!(table.pack(point), packed);
+        }
+        assert!(table.len() < 1000);
+    }
+
+    #[test]
+    fn prop_orbit_index_bijective() {
+        let mut checked = 0;
+        prop::check(200, |rng| {
+            let orbit = Point::new(prop::small_vec(rng, 3)).orbit;
+            let size = orbit.size();
+            if size > 20000 {
+                return;
+            }
+            checked += 1;
+            let mut seen = vec![false; size as usize];
+            for point in orbit.iter() {
+                let index = point.orbit_index();
+                assert!(index < size, "{index} >= {size} for {}", point.vec());
+                assert!(
+                    !seen[index as usize],
+                    "{index} repeated for {}",
+                    point.vec()
+                );
+                seen[index as usize] = true;
+            }
+            assert!(seen.into_iter().all(|x| x), "{:?}", orbit);
+        });
+        assert!(checked > 0);
     }
 }
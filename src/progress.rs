@@ -0,0 +1,328 @@
+use std::io::Stderr;
+use std::io::Write;
+use std::time::Duration;
+use std::time::Instant;
+
+/// How progress on writing an .off is reported to stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgressMode {
+    Quiet,
+    #[default]
+    Human,
+    /// One JSON object per line
+    Json,
+}
+
+/// Writer that counts the bytes passed through it
+#[derive(Debug)]
+pub struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self::starting_at(inner, 0)
+    }
+
+    /// Counting writer for a stream that already has `count` bytes in it
+    pub fn starting_at(inner: W, count: u64) -> Self {
+        Self { inner, count }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+// checking the clock on every line is measurable, so only check every so many elements
+const CLOCK_CHECK_ELEMENTS: u64 = 1024;
+
+/// Progress of a long write against its estimated size, reported to `out`
+#[derive(Debug)]
+pub struct Progress<W = Stderr> {
+    out: W,
+    mode: ProgressMode,
+    bytes_estimate: u64,
+    start: Instant,
+    last_report: Instant,
+    section: String,
+    elements_total: u64,
+    elements_done: u64,
+    bytes: u64,
+    start_bytes: u64,
+    finished: bool,
+}
+
+pub fn format_bytes(size: u64) -> String {
+    if size < 10_000_000 {
+        format!("{} kB", size / 1_000)
+    } else if size < 10_000_000_000 {
+        format!("{} MB", size / 1_000_000)
+    } else if size < 10_000_000_000_000 {
+        format!("{} GB", size / 1_000_000_000)
+    } else {
+        format!("{} TB", size / 1_000_000_000_000)
+    }
+}
+
+fn format_duration(secs: u64) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+fn json_string(st: &str) -> String {
+    format!("\"{}\"", st.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Progress {
+    pub fn new(mode: ProgressMode, bytes_estimate: u64) -> Self {
+        Self::to(std::io::stderr(), mode, bytes_estimate)
+    }
+}
+
+impl<W: Write> Progress<W> {
+    pub fn to(out: W, mode: ProgressMode, bytes_estimate: u64) -> Self {
+        let now = Instant::now();
+        Self {
+            out,
+            mode,
+            bytes_estimate,
+            start: now,
+            last_report: now,
+            section: String::new(),
+            elements_total: 0,
+            elements_done: 0,
+            bytes: 0,
+            start_bytes: 0,
+            finished: false,
+        }
+    }
+
+    /// Count only bytes after the first `bytes` towards the throughput, when resuming a write
+    pub fn resume_at(&mut self, bytes: u64) {
+        self.bytes = bytes;
+        self.start_bytes = bytes;
+    }
+
+    /// Start a run of `elements_total` lines, e.g. all faces of one face type
+    pub fn section(&mut self, section: String, elements_total: u64, bytes: u64) {
+        self.section = section;
+        self.elements_total = elements_total;
+        self.elements_done = 0;
+        self.bytes = bytes;
+        match self.mode {
+            ProgressMode::Quiet => {}
+            ProgressMode::Human => {
+                let line = format!("{} ({} elements)", self.section, elements_total);
+                self.line(&line);
+            }
+            ProgressMode::Json => self.report("section"),
+        }
+    }
+
+    /// Record `count` finished elements, with `bytes` written in total so far
+    pub fn elements(&mut self, count: u64, bytes: u64) {
+        let before = self.elements_done;
+        self.elements_done += count;
+        self.bytes = bytes;
+        if self.mode != ProgressMode::Quiet
+            && (before / CLOCK_CHECK_ELEMENTS != self.elements_done / CLOCK_CHECK_ELEMENTS
+                || count >= CLOCK_CHECK_ELEMENTS)
+            && self.last_report.elapsed() >= REPORT_INTERVAL
+        {
+            self.report("progress");
+        }
+    }
+
+    pub fn finish(&mut self, bytes: u64) {
+        self.bytes = bytes;
+        self.finished = true;
+        self.report("done");
+    }
+
+    fn bytes_per_sec(&self) -> f64 {
+        (self.bytes - self.start_bytes) as f64 / self.start.elapsed().as_secs_f64().max(1e-9)
+    }
+
+    fn eta_secs(&self) -> Option<u64> {
+        if self.finished {
+            return Some(0);
+        }
+        let rate = self.bytes_per_sec();
+        (rate > 0.0)
+            .then(|| (self.bytes_estimate.saturating_sub(self.bytes) as f64 / rate).round() as u64)
+    }
+
+    /// Write one line of progress, which is only informative, so a closed stderr does not stop
+    /// the write it is about
+    fn line(&mut self, line: &str) {
+        let _ = writeln!(self.out, "{line}");
+    }
+
+    fn report(&mut self, event: &str) {
+        self.last_report = Instant::now();
+        let line = match self.mode {
+            ProgressMode::Quiet => return,
+            ProgressMode::Human => {
+                let percent = 100.0 * self.bytes as f64 / self.bytes_estimate.max(1) as f64;
+                let eta = self.eta_secs().map_or("?".to_owned(), format_duration);
+                format!(
+                    "  {}/{} elements, {} of ~{} ({percent:.1}%), {}/s, elapsed {}, ETA {eta}",
+                    self.elements_done,
+                    self.elements_total,
+                    format_bytes(self.bytes),
+                    format_bytes(self.bytes_estimate),
+                    format_bytes(self.bytes_per_sec() as u64),
+                    format_duration(self.start.elapsed().as_secs()),
+                )
+            }
+            ProgressMode::Json => {
+                format!(
+                    "{{\"event\":{},\"section\":{},\"elements_done\":{},\"elements_total\":{},\"bytes\":{},\"bytes_estimate\":{},\"bytes_per_sec\":{:.0},\"elapsed_secs\":{:.3},\"eta_secs\":{}}}",
+                    json_string(event),
+                    json_string(&self.section),
+                    self.elements_done,
+                    self.elements_total,
+                    self.bytes,
+                    self.bytes_estimate,
+                    self.bytes_per_sec(),
+                    self.start.elapsed().as_secs_f64(),
+                    self.eta_secs()
+                        .map_or("null".to_owned(), |eta| eta.to_string()),
+                )
+            }
+        };
+        self.line(&line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting_writer_counts() {
+        let mut off = Vec::new();
+        let mut writer = CountingWriter::new(&mut off);
+        write!(writer, "8OFF\n{} {}\n", 12, 345).unwrap();
+        assert_eq!(writer.count(), 12);
+        assert_eq!(off.len(), 12);
+    }
+
+    /// Progress into a buffer, as if it had started `secs` seconds ago
+    fn started_ago(mode: ProgressMode, bytes_estimate: u64, secs: u64) -> Progress<Vec<u8>> {
+        let mut progress = Progress::to(Vec::new(), mode, bytes_estimate);
+        let then = Instant::now()
+            .checked_sub(Duration::from_secs(secs))
+            .unwrap();
+        progress.start = then;
+        progress.last_report = then;
+        progress
+    }
+
+    fn lines(progress: &Progress<Vec<u8>>) -> Vec<&str> {
+        std::str::from_utf8(&progress.out)
+            .unwrap()
+            .lines()
+            .collect()
+    }
+
+    #[test]
+    fn quiet_writes_nothing() {
+        let mut progress = started_ago(ProgressMode::Quiet, 3000, 10);
+        progress.section("Vertices".to_owned(), 2048, 0);
+        progress.elements(2048, 1000);
+        progress.finish(3000);
+        assert!(progress.out.is_empty());
+    }
+
+    #[test]
+    fn throughput_and_eta() {
+        let mut progress = started_ago(ProgressMode::Json, 3000, 10);
+        assert_eq!(progress.eta_secs(), None);
+        progress.elements(10, 1000);
+        // 1000 bytes in a bit over 10 seconds, and 2000 to go
+        assert!((99.0..=100.0).contains(&progress.bytes_per_sec()));
+        assert_eq!(progress.eta_secs(), Some(20));
+
+        // bytes kept from before a resume do not count towards the throughput
+        let mut progress = started_ago(ProgressMode::Json, 3000, 10);
+        progress.resume_at(500);
+        progress.elements(10, 1500);
+        assert!((99.0..=100.0).contains(&progress.bytes_per_sec()));
+        assert_eq!(progress.eta_secs(), Some(15));
+        progress.finish(3000);
+        assert_eq!(progress.eta_secs(), Some(0));
+    }
+
+    #[test]
+    fn json_lines() {
+        let mut progress = started_ago(ProgressMode::Json, 3000, 10);
+        progress.section(r#"Faces: "A0""#.to_owned(), 4096, 0);
+        progress.last_report -= REPORT_INTERVAL;
+        // reported once a multiple of the clock check is crossed, at most once a second
+        progress.elements(1000, 500);
+        progress.elements(1048, 1000);
+        progress.elements(2048, 2000);
+        progress.finish(3000);
+
+        // the elapsed time, which is only about 10 seconds, taken out of each line
+        let lines: Vec<_> = lines(&progress)
+            .into_iter()
+            .map(|line| {
+                let (before, rest) = line.split_once(r#""elapsed_secs":"#).unwrap();
+                let (elapsed, after) = rest.split_once(',').unwrap();
+                let elapsed: f64 = elapsed.parse().unwrap();
+                assert!((10.0..11.0).contains(&elapsed), "{line}");
+                format!("{before}{after}")
+            })
+            .collect();
+        let section = r#""section":"Faces: \"A0\"""#;
+        assert_eq!(
+            lines,
+            [
+                format!(
+                    r#"{{"event":"section",{section},"elements_done":0,"elements_total":4096,"bytes":0,"bytes_estimate":3000,"bytes_per_sec":0,"eta_secs":null}}"#
+                ),
+                format!(
+                    r#"{{"event":"progress",{section},"elements_done":2048,"elements_total":4096,"bytes":1000,"bytes_estimate":3000,"bytes_per_sec":100,"eta_secs":20}}"#
+                ),
+                format!(
+                    r#"{{"event":"done",{section},"elements_done":4096,"elements_total":4096,"bytes":3000,"bytes_estimate":3000,"bytes_per_sec":300,"eta_secs":0}}"#
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn human_lines() {
+        let mut progress = started_ago(ProgressMode::Human, 30_000_000, 10);
+        progress.section("Vertices".to_owned(), 2048, 0);
+        progress.elements(2048, 9_999_999);
+        assert_eq!(
+            lines(&progress),
+            [
+                "Vertices (2048 elements)",
+                "  2048/2048 elements, 9999 kB of ~30 MB (33.3%), 999 kB/s, elapsed 0:00:10, ETA 0:00:20",
+            ]
+        );
+    }
+
+    #[test]
+    fn json_string_escapes() {
+        assert_eq!(json_string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+}
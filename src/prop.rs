@@ -0,0 +1,95 @@
+//! Seedable randomized property checks.
+//!
+//! Every case gets its own seed derived from a base seed, which defaults to a fixed value and can
+//! be overridden with the `E8_PROP_SEED` environment variable. A failing case reports its seed, so
+//! rerunning with `E8_PROP_SEED=<seed>` reproduces it as case 0.
+
+use crate::point::AxSign;
+use crate::point::B8;
+use crate::point::Coord;
+use crate::point::D8;
+use crate::point::S8;
+use crate::point::Symmetry;
+use crate::point::Vec8;
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+use std::panic::AssertUnwindSafe;
+use std::panic::catch_unwind;
+use std::panic::resume_unwind;
+
+const DEFAULT_SEED: u64 = 0xe8e8_e8e8;
+
+fn base_seed() -> u64 {
+    std::env::var("E8_PROP_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(DEFAULT_SEED)
+}
+
+/// Run `prop` on `cases` independently seeded rngs
+pub fn check(cases: u64, mut prop: impl FnMut(&mut SmallRng)) {
+    let base = base_seed();
+    for case in 0..cases {
+        let seed = base.wrapping_add(case);
+        let mut rng = SmallRng::seed_from_u64(seed);
+        if let Err(err) = catch_unwind(AssertUnwindSafe(|| prop(&mut rng))) {
+            eprintln!("property failed on case {case}, rerun with E8_PROP_SEED={seed}");
+            resume_unwind(err);
+        }
+    }
+}
+
+/// Whether `vec` is in the crate's E8 lattice: all coordinates even or all odd, sum divisible by 4
+pub fn in_lattice(vec: Vec8) -> bool {
+    let parity = vec[0].rem_euclid(2);
+    vec.iter().all(|x| x.rem_euclid(2) == parity) && vec.sum().rem_euclid(4) == 0
+}
+
+/// Random lattice vector with coordinates of absolute value at most about `bound`
+pub fn lattice_vec(rng: &mut impl Rng, bound: Coord) -> Vec8 {
+    let parity = rng.random_range(0..2);
+    let mut vec: Vec8 = [(); 8]
+        .map(|_| 2 * rng.random_range(-bound / 2..=bound / 2) + parity)
+        .into();
+    if vec.sum().rem_euclid(4) != 0 {
+        vec[rng.random_range(0..8)] += 2;
+    }
+    vec
+}
+
+/// Random vector with no lattice constraint, with small coordinates so that orbits have repeats
+pub fn small_vec(rng: &mut impl Rng, bound: Coord) -> Vec8 {
+    [(); 8].map(|_| rng.random_range(-bound..=bound)).into()
+}
+
+/// Uniformly random element of D8
+pub fn d8(rng: &mut impl Rng) -> D8 {
+    let mut axs = [0, 1, 2, 3, 4, 5, 6, 7];
+    axs.shuffle(rng);
+    let mut flips = 0;
+    let mut axs = axs.map(|ax| {
+        let sign = if rng.random() { 1 } else { -1 };
+        if sign == -1 {
+            flips += 1;
+        }
+        AxSign::new(ax, sign)
+    });
+    if flips % 2 != 0 {
+        axs[0].flip_sign();
+    }
+    D8::new(axs)
+}
+
+/// Uniformly random element of B8
+pub fn b8(rng: &mut impl Rng) -> B8 {
+    let mut axs = d8(rng).axes();
+    if rng.random() {
+        axs[0].flip_sign();
+    }
+    B8::from_axes(axs)
+}
+
+/// Uniformly random element of S8
+pub fn s8(rng: &mut impl Rng) -> S8 {
+    S8::from_axes(d8(rng).axes().map(|p| AxSign::new(p.ax(), 1)))
+}
@@ -0,0 +1,201 @@
+//! Splitting an .off into shards that can be written on separate machines.
+//!
+//! The element lines are split into `count` contiguous runs with about the same number of lines
+//! each. Every shard file starts with a line saying which shard it is, followed by exactly the
+//! text the full .off has for its run, with the global indices. The first shard also has the 8OFF
+//! header, so [`merge_shards`] only has to check the shards and concatenate them.
+
+use crate::compress::Compression;
+use crate::coords::Coordinates;
+use crate::e8::MirrorSet;
+use crate::error::Error;
+use crate::error::Result;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Shard `index` of `count`, written `index/count` with `index` starting from 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shard {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl Shard {
+    /// Range of elements of this shard among `total` elements
+    pub fn elements(self, total: u64) -> (u64, u64) {
+        let split = |i: u64| (total as u128 * i as u128 / self.count as u128) as u64;
+        (split(self.index), split(self.index + 1))
+    }
+
+    pub fn is_last(self) -> bool {
+        self.index + 1 == self.count
+    }
+}
+
+impl Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+impl FromStr for Shard {
+    type Err = Error;
+    fn from_str(st: &str) -> Result<Self> {
+        let bad = || Error::Shard(format!("bad shard {st:?}, expected i/n with 0 <= i < n"));
+        let (index, count) = st.split_once('/').ok_or_else(bad)?;
+        let shard = Shard {
+            index: index.parse().map_err(|_| bad())?,
+            count: count.parse().map_err(|_| bad())?,
+        };
+        if shard.index >= shard.count {
+            return Err(bad());
+        }
+        Ok(shard)
+    }
+}
+
+/// First line of a shard file
+pub fn shard_line(
+    shard: Shard,
+    mirrors: MirrorSet,
+    dim_limit: usize,
+    oriented: bool,
+    coordinates: Coordinates,
+) -> String {
+    let mut line = format!(
+        "# shard {shard} mirrors {} dim_limit {dim_limit}",
+        mirrors.bits()
+    );
+    if coordinates != Coordinates::default() {
+        line += &format!(" coordinates {coordinates}");
+    }
+    if oriented {
+        line += " oriented";
+    }
+    line + "\n"
+}
+
+/// The shard, mirrors, dimension limit, orientation and coordinates of a shard line
+type ShardHeader = (Shard, u8, usize, bool, Coordinates);
+
+fn parse_shard_line(line: &str, path: &Path) -> Result<ShardHeader> {
+    let bad = || {
+        Error::Shard(format!(
+            "{} does not start with a shard line",
+            path.display()
+        ))
+    };
+    let mut words: Vec<_> = line.split_whitespace().collect();
+    let oriented = words.last() == Some(&"oriented");
+    if oriented {
+        words.pop();
+    }
+    let coordinates = match words[..] {
+        [.., "coordinates", coordinates] => {
+            words.truncate(words.len() - 2);
+            coordinates.parse()?
+        }
+        _ => Coordinates::default(),
+    };
+    let &[
+        "#",
+        "shard",
+        shard,
+        "mirrors",
+        mirrors,
+        "dim_limit",
+        dim_limit,
+    ] = &words[..]
+    else {
+        return Err(bad());
+    };
+    Ok((
+        shard.parse()?,
+        mirrors.parse().map_err(|_| bad())?,
+        dim_limit.parse().map_err(|_| bad())?,
+        oriented,
+        coordinates,
+    ))
+}
+
+/// Stitch the shard files at `paths`, in any order, into one .off, decompressing any that have
+/// the extension of a [`Compression`]
+pub fn merge_shards(paths: &[impl AsRef<Path>], mut writer: impl Write) -> Result<()> {
+    let mut shards = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let mut reader = BufReader::new(Compression::from_path(path).reader(File::open(path)?)?);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        shards.push((parse_shard_line(&line, path)?, reader));
+    }
+    shards.sort_by_key(|((shard, ..), _)| shard.index);
+
+    let Some(&(first, ..)) = shards.first().map(|(header, _)| header) else {
+        return Err(Error::Shard("no shards to merge".to_owned()));
+    };
+    // the same split of the same .off, up to the shard
+    let split = |&(shard, mirrors, dim_limit, oriented, coordinates): &ShardHeader| {
+        (shard.count, mirrors, dim_limit, oriented, coordinates)
+    };
+    for (i, (header, _)) in shards.iter().enumerate() {
+        let shard = header.0;
+        if split(header) != split(&shards[0].0) {
+            return Err(Error::Shard(format!(
+                "shard {shard} is from a different split than shard {first}"
+            )));
+        }
+        if shard.index != i as u64 {
+            return Err(Error::Shard(format!(
+                "shard {i}/{} is missing",
+                first.count
+            )));
+        }
+    }
+    if shards.len() as u64 != first.count {
+        return Err(Error::Shard(format!(
+            "shard {}/{} is missing",
+            shards.len(),
+            first.count
+        )));
+    }
+
+    for (_, mut reader) in shards {
+        std::io::copy(&mut reader, &mut writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shard_elements_cover() {
+        let total = 1000003;
+        let count = 7;
+        let mut next = 0;
+        for index in 0..count {
+            let (start, end) = Shard { index, count }.elements(total);
+            assert_eq!(start, next);
+            assert!(end - start <= total / count + 1);
+            next = end;
+        }
+        assert_eq!(next, total);
+    }
+
+    #[test]
+    fn shard_parse() {
+        assert_eq!(
+            "2/5".parse::<Shard>().unwrap(),
+            Shard { index: 2, count: 5 }
+        );
+        assert!("5/5".parse::<Shard>().is_err());
+        assert!("5".parse::<Shard>().is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Checking that an .off fits before writing it.
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::progress::format_bytes;
+use std::path::Path;
+
+/// Parse a size in bytes, with an optional decimal unit `k`, `M`, `G` or `T` and an optional
+/// trailing `B`, like `500G` or `1.5TB`
+pub fn parse_size(st: &str) -> Result<u64> {
+    let bad = || Error::Space(format!("bad size {st:?}, expected a number like 500G"));
+    let number = st.strip_suffix('B').unwrap_or(st);
+    let (number, unit) = match number.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&number[..i], 1e3),
+        Some((i, 'M')) => (&number[..i], 1e6),
+        Some((i, 'G')) => (&number[..i], 1e9),
+        Some((i, 'T')) => (&number[..i], 1e12),
+        _ => (number, 1.0),
+    };
+    if let Ok(bytes) = number.parse::<u64>() {
+        return Ok(bytes.saturating_mul(unit as u64));
+    }
+    let number: f64 = number.parse().map_err(|_| bad())?;
+    if number.is_nan() || number < 0.0 {
+        return Err(bad());
+    }
+    Ok((number * unit) as u64)
+}
+
+/// Check that an output of `size` bytes can be written to `path`, within `max_size` and the
+/// free space of its filesystem
+///
+/// The first `kept` bytes of an existing `path` are kept, as when resuming, and the rest of it
+/// is freed before writing.
+pub fn check_space(path: &Path, size: u64, kept: u64, max_size: Option<u64>) -> Result<()> {
+    if let Some(max_size) = max_size
+        && size > max_size
+    {
+        return Err(Error::Space(format!(
+            "{} would take {}, over the budget of {}",
+            path.display(),
+            format_bytes(size),
+            format_bytes(max_size)
+        )));
+    }
+
+    // devices and pipes like /dev/stdout take no space where they are
+    let metadata = path.metadata().ok();
+    if metadata
+        .as_ref()
+        .is_some_and(|metadata| !metadata.is_file())
+    {
+        return Ok(());
+    }
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let existing = metadata.map_or(0, |metadata| metadata.len());
+    let available = fs4::available_space(dir)? + existing.saturating_sub(kept);
+    let needed = size.saturating_sub(kept);
+    if needed > available {
+        return Err(Error::Space(format!(
+            "{} needs {} more but only {} is free on its filesystem",
+            path.display(),
+            format_bytes(needed),
+            format_bytes(available)
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sizes() {
+        assert_eq!(parse_size("123").unwrap(), 123);
+        assert_eq!(parse_size("500G").unwrap(), 500_000_000_000);
+        assert_eq!(parse_size("1.5TB").unwrap(), 1_500_000_000_000);
+        assert_eq!(parse_size("64kB").unwrap(), 64_000);
+        assert!(parse_size("G").is_err());
+        assert!(parse_size("-1M").is_err());
+        assert!(parse_size("12X").is_err());
+    }
+
+    #[test]
+    fn check_space_limits() {
+        let path = std::env::temp_dir().join(format!("e8-space-{}.off", std::process::id()));
+        check_space(&path, 1000, 0, Some(1000)).unwrap();
+        assert!(matches!(
+            check_space(&path, 1001, 0, Some(1000)),
+            Err(Error::Space(_))
+        ));
+        assert!(matches!(
+            check_space(&path, u64::MAX, 0, None),
+            Err(Error::Space(_))
+        ));
+        check_space(Path::new("/dev/null"), u64::MAX, 0, None).unwrap();
+    }
+}
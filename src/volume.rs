@@ -0,0 +1,284 @@
+//! Exact volumes of a polytope, its facets and the faces of every other type.
+//!
+//! The `k`-volume of a face is a sum of pyramids over its `k - 1`-faces with apex at its center,
+//! each `1/k` of its height times the volume of its base. The subfaces of one type in a face are
+//! congruent, and there are as many of them as the index of their stabilizer in the group of the
+//! face. A pyramid's height squared is the difference of the squared distances from
+//! [`MirrorSet::vertex`] to the centers of the face and the subface, which are rational, so the
+//! volumes come out as exact [`SurdSum`]s from the face types alone, without any elements.
+
+use crate::coords::Coordinates;
+use crate::e8::MirrorSet;
+use crate::error::Error;
+use crate::error::Result;
+use crate::metric::Surd;
+use crate::metric::SurdSum;
+use crate::metric::gcd;
+use crate::metric::widen;
+use fxhash::FxHashMap;
+
+/// Determinant of a square matrix, by Bareiss' fraction-free elimination
+pub(crate) fn determinant(mut matrix: Vec<Vec<i128>>) -> i128 {
+    let n = matrix.len();
+    let mut previous = 1;
+    let mut sign = 1;
+    for k in 0..n.saturating_sub(1) {
+        // a zero pivot is swapped with a row below, and without one the columns are dependent
+        let Some(pivot) = (k..n).find(|&i| matrix[i][k] != 0) else {
+            return 0;
+        };
+        if pivot != k {
+            matrix.swap(pivot, k);
+            sign = -sign;
+        }
+        for i in k + 1..n {
+            for j in k + 1..n {
+                matrix[i][j] =
+                    (matrix[i][j] * matrix[k][k] - matrix[i][k] * matrix[k][j]) / previous;
+            }
+        }
+        previous = matrix[k][k];
+    }
+    sign * matrix
+        .last()
+        .and_then(|row| row.last())
+        .copied()
+        .unwrap_or(1)
+}
+
+/// All the faces of one type, with their volume
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaceVolume {
+    pub face_type: MirrorSet,
+    pub count: u64,
+    pub volume: SurdSum,
+}
+
+/// Volume of a polytope, of its facets and of its whole boundary, with lengths in some
+/// [`Coordinates`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Volumes {
+    /// The 8-volume
+    pub volume: SurdSum,
+    /// The 7-volume of each facet, by facet type in the order of [`MirrorSet::face_types`]
+    pub facets: Vec<FaceVolume>,
+    /// The 7-volume of all the facets together
+    pub surface: SurdSum,
+}
+
+impl MirrorSet {
+    /// Squared distance from [`vertex`](Self::vertex) to the center of the face of type
+    /// `face_type` around it, the squared norm of its projection onto the span of the poles, as
+    /// a fraction
+    fn center_distance_squared(self, face_type: Self) -> (i128, i128) {
+        let poles: Vec<_> = face_type.mirrors().map(|m| widen(m.pole())).collect();
+        let vertex = widen(self.vertex().vec());
+        let n = poles.len();
+        // the Gram matrix of the poles bordered by their dot products with the vertex, whose
+        // determinant is minus the Gram determinant times the projection squared
+        let mut bordered = vec![vec![0; n + 1]; n + 1];
+        for a in 0..n {
+            for b in 0..n {
+                bordered[a][b] = poles[a].dot(&poles[b]) as i128;
+            }
+            let dot = poles[a].dot(&vertex) as i128;
+            bordered[a][n] = dot;
+            bordered[n][a] = dot;
+        }
+        let gram = bordered[..n].iter().map(|row| row[..n].to_vec()).collect();
+        (-determinant(bordered), determinant(gram))
+    }
+
+    /// Number of subfaces of type `subface_type` in a face of type `face_type`
+    pub fn subface_count(self, face_type: Self, subface_type: Self) -> u64 {
+        // the stabilizer of the subface is generated by the mirrors fixing its center
+        face_type.order() / (face_type - self.face_center(subface_type)).order()
+    }
+
+    /// Volume of the face of each type in `face_types`, which are
+    /// [`face_types`](Self::face_types), in the same order, with lengths in `coordinates`
+    pub fn face_volumes(
+        self,
+        face_types: &[Vec<Self>; 9],
+        coordinates: Coordinates,
+    ) -> Result<[Vec<SurdSum>; 9]> {
+        let (scale_num, scale_den) = self.length_squared_scale(coordinates)?;
+        let scale = Surd::sqrt(false, scale_num, scale_den);
+        let distances: FxHashMap<_, _> = face_types
+            .iter()
+            .flatten()
+            .map(|&face_type| (face_type, self.center_distance_squared(face_type)))
+            .collect();
+
+        // height of the pyramid over a subface with apex at the center of the face
+        let height = |face_type: Self, subface_type: Self| -> Result<Surd> {
+            let (num, den) = distances[&face_type];
+            let (sub_num, sub_den) = distances[&subface_type];
+            let (square_num, square_den) = (num * sub_den - sub_num * den, den * sub_den);
+            let g = gcd(square_num.unsigned_abs(), square_den.unsigned_abs()) as i128;
+            let small = |x: i128| {
+                u64::try_from(x / g).map_err(|_| {
+                    Error::Invariant(format!(
+                        "height from {face_type:?} to {subface_type:?} squared is \
+                         {square_num}/{square_den}"
+                    ))
+                })
+            };
+            Ok(Surd::sqrt(false, small(square_num)?, small(square_den)?))
+        };
+
+        let mut volumes: [Vec<SurdSum>; 9] = Default::default();
+        volumes[0] = vec![Surd::rational(1, 1).into(); face_types[0].len()];
+        for k in 1..=8 {
+            volumes[k] = face_types[k]
+                .iter()
+                .map(|&face_type| {
+                    face_types[k - 1]
+                        .iter()
+                        .zip(&volumes[k - 1])
+                        .filter(|&(&subface_type, _)| face_type.contains(subface_type))
+                        .map(|(&subface_type, volume)| {
+                            let count = self.subface_count(face_type, subface_type);
+                            let pyramid = height(face_type, subface_type)?
+                                * scale
+                                * Surd::rational(count.into(), k as u128);
+                            Ok(volume.clone() * pyramid)
+                        })
+                        .sum()
+                })
+                .collect::<Result<_>>()?;
+        }
+        Ok(volumes)
+    }
+
+    /// The 8-volume and the 7-volumes of the facets and the boundary, with lengths in
+    /// `coordinates`
+    pub fn volumes(self, coordinates: Coordinates) -> Result<Volumes> {
+        let face_types = self.face_types();
+        let [.., facet_volumes, volume] = self.face_volumes(&face_types, coordinates)?;
+        let facets: Vec<_> = face_types[7]
+            .iter()
+            .zip(facet_volumes)
+            .map(|(&face_type, volume)| FaceVolume {
+                face_type,
+                count: self.face_center(face_type).vertex_count(),
+                volume,
+            })
+            .collect();
+        let surface = facets
+            .iter()
+            .map(|facet| facet.volume.clone() * Surd::rational(facet.count.into(), 1))
+            .sum();
+        Ok(Volumes {
+            volume: volume.into_iter().next().unwrap_or_default(),
+            facets,
+            surface,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orient::to_row;
+
+    #[test]
+    fn volumes_of_4_21() {
+        let volumes = MirrorSet::A0.volumes(Coordinates::Unit(9)).unwrap();
+        // 7-simplices and 7-orthoplexes
+        let facets: Vec<_> = volumes
+            .facets
+            .iter()
+            .map(|facet| (facet.face_type, facet.count, facet.volume.to_string()))
+            .collect();
+        assert_eq!(
+            facets,
+            [
+                (MirrorSet::all() - MirrorSet::C, 17280, "1/20160".to_owned()),
+                (MirrorSet::all() - MirrorSet::B0, 2160, "√2/630".to_owned()),
+            ]
+        );
+        assert_eq!(volumes.surface.to_string(), "6/7 + 24√2/7");
+
+        // pyramids from the center over the facets
+        let metrics = MirrorSet::A0.metrics(Coordinates::Unit(9)).unwrap();
+        let pyramids: f64 = volumes
+            .facets
+            .iter()
+            .zip(&metrics.facet_distances)
+            .map(|(facet, distance)| {
+                facet.count as f64 * facet.volume.to_f64() * distance.distance.to_f64() / 8.0
+            })
+            .sum();
+        assert!((volumes.volume.to_f64() - pyramids).abs() < 1e-9);
+        // the vertices are lattice points, so the volume is rational
+        assert_eq!(volumes.volume.0.len(), 1);
+        assert_eq!(volumes.volume.0[0].rad, 1);
+    }
+
+    #[test]
+    fn faces_of_the_omnitruncate() {
+        let mirrors = MirrorSet::all();
+        let face_types = mirrors.face_types();
+        let volumes = mirrors
+            .face_volumes(&face_types, Coordinates::Unit(9))
+            .unwrap();
+        let volume = |face_type: MirrorSet| {
+            let k = face_type.size() as usize;
+            let i = face_types[k].iter().position(|&t| t == face_type).unwrap();
+            volumes[k][i].to_string()
+        };
+        assert_eq!(volume(MirrorSet::A0), "1");
+        // square, hexagon, octagon and truncated octahedron
+        assert_eq!(volume(MirrorSet::A0 | MirrorSet::A2), "1");
+        assert_eq!(volume(MirrorSet::A0 | MirrorSet::A1), "3√3/2");
+        assert_eq!(volume(MirrorSet::A0 | MirrorSet::A1 | MirrorSet::A2), "8√2");
+        assert!(volumes.iter().flatten().all(|v| v.to_f64() > 0.0));
+    }
+
+    #[test]
+    fn polygon_areas_and_subface_counts() {
+        for mirrors in [
+            MirrorSet::A0 | MirrorSet::M,
+            MirrorSet::B1 | MirrorSet::A3,
+            MirrorSet::C | MirrorSet::A1 | MirrorSet::B0,
+        ] {
+            let face_types = mirrors.face_types();
+            let volumes = mirrors
+                .face_volumes(&face_types, Coordinates::Lattice)
+                .unwrap();
+            for (&face_type, area) in face_types[2].iter().zip(&volumes[2]) {
+                // shoelace formula in the plane of the polygon
+                let vertices: Vec<_> = mirrors
+                    .polygon(face_type)
+                    .unwrap()
+                    .iter()
+                    .map(|v| to_row(v.vec()))
+                    .collect();
+                let shoelace = (1..vertices.len() - 1)
+                    .map(|i| {
+                        let (a, b) = (vertices[i] - vertices[0], vertices[i + 1] - vertices[0]);
+                        (a.norm_squared() * b.norm_squared() - a.dot(&b).powi(2)).sqrt() / 2.0
+                    })
+                    .sum::<f64>();
+                assert!((area.to_f64() - shoelace).abs() < 1e-9, "{face_type:?}");
+            }
+            for k in 1..=6 {
+                for &face_type in &face_types[k] {
+                    for &subface_type in &face_types[k - 1] {
+                        if face_type.contains(subface_type) {
+                            let orbit = MirrorSet::orbit_under(
+                                mirrors.face_center(subface_type).vertex(),
+                                face_type,
+                            );
+                            assert_eq!(
+                                mirrors.subface_count(face_type, subface_type),
+                                orbit.len() as u64
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}